@@ -2,7 +2,8 @@
 
 use std::str::FromStr;
 use egrikor::*;
-use egrikor::widgets::lists::row;
+use egrikor::widgets::drawables::text;
+use egrikor::widgets::lists::{row, CrossAxisAlignment};
 use egrikor::widgets::textbox::{textbox, TextBoxContent};
 
 fn example() -> impl Widget<Runtime> {
@@ -27,6 +28,7 @@ fn example() -> impl Widget<Runtime> {
                     }
                 },
             ),
+            fixed => text("Celsius ="),
             textbox::<(&mut State, &mut E)>(
                 |e| &mut e.0.fahrenheit,
                 |e| {
@@ -38,13 +40,16 @@ fn example() -> impl Widget<Runtime> {
                     }
                 },
             ),
+            fixed => text("Fahrenheit"),
         ])
+        .spacing(4.)
+        .cross_axis_alignment(CrossAxisAlignment::Baseline)
     })
 }
 
 fn main() {
-    run(
-        "7GUIs TempConv",
-        example(),
-    );
+    AppLauncher::new("7GUIs TempConv")
+        .with_size((400., 60.))
+        .resizable(false)
+        .run(example());
 }
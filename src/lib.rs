@@ -1,29 +1,52 @@
 #![feature(type_alias_impl_trait)]
 
 use std::fmt::Debug;
+use std::time::Duration;
 
 use druid_shell::{
-    kurbo::{Point, Rect, Size},
-    Application, KeyEvent, Modifiers, MouseEvent, Region, WinHandler, WindowBuilder, WindowHandle,
+    kurbo::{Point, Rect, Size, Vec2},
+    Application, HotKey, KbKey, KeyEvent, Modifiers, MouseEvent, Region, SysMods, TimerToken,
+    WinHandler, WindowBuilder, WindowHandle,
 };
 use piet_common::{Color, Piet, RenderContext, PietText};
-use widgets::{RectTheme, TextTheme, Theme, WidgetTheme, WidgetVariants};
+use text::{Clipboard, SystemClipboard};
+use widgets::Theme;
 
 pub mod pass_widget;
+mod runtime;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod text;
 pub mod theme;
 pub mod widgets;
 
+pub use runtime::{run, run_with_env, AppLauncher, Runtime, WindowHandler, WindowId};
+
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum CursorInput {
     Down(MouseButton),
     Up(MouseButton),
     Move,
+    /// The scroll wheel (or a touchpad) moved by the given delta. Positive values scroll down and to
+    /// the right.
+    Wheel(Vec2),
 }
 
+/// The keyboard input the focused widget gets. druid-shell 0.7 doesn't expose the input method
+/// yet, so windows only send [KeyboardInput::KeyDown] so far. The composition variants can be sent
+/// with [TestHarness::keyboard_input](testing::TestHarness::keyboard_input) until it does.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum KeyboardInput {
     KeyDown(druid_shell::KeyEvent),
+    /// An input method started composing text, for example to turn a sequence of latin characters
+    /// into Japanese.
+    CompositionStart,
+    /// The text being composed changed. It should be shown in place of the selection, but it isn't
+    /// final yet.
+    CompositionUpdate(String),
+    /// The composition is done and the given text should be inserted. An empty string means the
+    /// composition was cancelled.
+    CompositionCommit(String),
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -62,6 +85,12 @@ impl Default for InputState {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FocusDirection {
+    Next,
+    Previous,
+}
+
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub struct InputReturn {
     pub demand_focus: bool,
@@ -75,6 +104,80 @@ impl Default for InputReturn {
     }
 }
 
+/// Gives input handlers access to the services of the window they run in. The
+/// [TestHarness](testing::TestHarness) provides fakes for them.
+#[non_exhaustive]
+pub struct InputCtx<'a> {
+    pub clipboard: &'a mut dyn Clipboard,
+    pub timers: &'a mut dyn Timers,
+    /// The parts of the window that have to be repainted after the input was handled.
+    pub damage: &'a mut Region,
+    /// How much of the widget tree has to be laid out again after the input was handled.
+    pub relayout: &'a mut Relayout,
+    /// Set once a widget scrolled because of a [CursorInput::Wheel], so the scroll areas it is
+    /// nested in leave the wheel alone.
+    pub wheel_handled: bool,
+}
+
+impl<'a> InputCtx<'a> {
+    pub fn request_timer(&mut self, after: Duration) -> TimerToken {
+        self.timers.request_timer(after)
+    }
+
+    /// Marks `rect` for repainting because what is shown there changed.
+    pub fn invalidate_rect(&mut self, rect: Rect) {
+        self.damage.add_rect(rect);
+    }
+
+    /// Marks the whole window for repainting and the whole tree for layout. This is needed when a
+    /// change can show up anywhere, for example after a callback changed the environment.
+    pub fn invalidate(&mut self) {
+        self.invalidate_window();
+        *self.relayout = Relayout::All;
+    }
+
+    /// Marks the whole window for repainting without laying anything out again, for changes that
+    /// only affect how widgets look, like the focus moving.
+    pub fn invalidate_window(&mut self) {
+        self.damage.add_rect(Rect::new(
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+            f64::INFINITY,
+            f64::INFINITY,
+        ));
+    }
+
+    /// Asks for the widget handling the input to be laid out again, because something its layout
+    /// depends on changed. The containers it is in are laid out again as well.
+    pub fn request_layout(&mut self) {
+        *self.relayout = (*self.relayout).max(Relayout::Dirty);
+    }
+}
+
+/// How much of the widget tree has to be laid out again.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Relayout {
+    /// The last layout is still up to date.
+    None,
+    /// Only the widgets that asked for it with [InputCtx::request_layout] and the containers they
+    /// are in.
+    Dirty,
+    /// Everything, for example because the window size or the environment changed.
+    All,
+}
+
+/// Schedules timers. When a timer fires its token is passed to [Widget::handle_timer].
+pub trait Timers {
+    /// Requests a timer that fires once after `after` has passed.
+    fn request_timer(&mut self, after: Duration) -> TimerToken;
+}
+
+impl Timers for WindowHandle {
+    fn request_timer(&mut self, after: Duration) -> TimerToken {
+        WindowHandle::request_timer(self, after)
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct Context {
     pub window_size: [f64; 2],
@@ -82,15 +185,90 @@ pub struct Context {
 
 // type LayoutConstraint = LayoutConstraint;
 
-#[derive(Copy, Clone, Debug)]
+/// The space a widget is given in [Widget::layout].
+///
+/// The size a widget reports through [WidgetState::min_size] afterwards is the least space it
+/// needs and not the space it gets. Containers are free to render it in a bigger rect, for example
+/// to stretch all children of a row to the height of the tallest one. That size must be at least
+/// `min` and should stay within `max`. Only content that can't be shrunk any further, like a line
+/// of text, may go beyond `max`, and then it is up to the container what to do with it.
+///
+/// An axis without an upper limit has an infinite `max`.
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct LayoutConstraint {
-    pub x: Option<f64>,
-    pub y: Option<f64>,
+    pub min: Size,
+    pub max: Size,
 }
 
 impl LayoutConstraint {
-    pub const fn new(x: Option<f64>, y: Option<f64>) -> Self {
-        Self { x, y }
+    /// No limits on either axis.
+    pub const UNBOUNDED: Self = Self::loose(Size {
+        width: f64::INFINITY,
+        height: f64::INFINITY,
+    });
+
+    pub const fn new(min: Size, max: Size) -> Self {
+        Self { min, max }
+    }
+
+    /// Exactly `size`.
+    pub const fn tight(size: Size) -> Self {
+        Self {
+            min: size,
+            max: size,
+        }
+    }
+
+    /// Anything from nothing up to `max`.
+    pub const fn loose(max: Size) -> Self {
+        Self {
+            min: Size::ZERO,
+            max,
+        }
+    }
+
+    /// The same upper limits without the lower ones.
+    pub fn loosen(self) -> Self {
+        Self::loose(self.max)
+    }
+
+    pub fn is_width_bounded(&self) -> bool {
+        self.max.width.is_finite()
+    }
+
+    pub fn is_height_bounded(&self) -> bool {
+        self.max.height.is_finite()
+    }
+
+    /// Shrinks both limits by `amount`, for example to leave room for padding around a child.
+    pub fn deflate(self, amount: Size) -> Self {
+        Self {
+            min: Size::new(
+                (self.min.width - amount.width).max(0.),
+                (self.min.height - amount.height).max(0.),
+            ),
+            max: Size::new(
+                (self.max.width - amount.width).max(0.),
+                (self.max.height - amount.height).max(0.),
+            ),
+        }
+    }
+
+    /// Clamps `size` to fit the constraint. This is for widgets that can make do with any size.
+    pub fn constrain(&self, size: Size) -> Size {
+        Size::new(
+            size.width.max(self.min.width).min(self.max.width),
+            size.height.max(self.min.height).min(self.max.height),
+        )
+    }
+
+    /// Grows `size` to at least `min`, but leaves it bigger than `max` if it is. This is for
+    /// widgets with content that can't be shrunk.
+    pub fn constrain_min(&self, size: Size) -> Size {
+        Size::new(
+            size.width.max(self.min.width),
+            size.height.max(self.min.height),
+        )
     }
 }
 
@@ -98,6 +276,71 @@ impl LayoutConstraint {
 pub struct LayoutCtx<'a, 't> {
     pub text: &'a mut PietText,
     pub theme: &'t Theme,
+    pub timers: &'a mut dyn Timers,
+    /// Whether this layout pass was started because of [Relayout::Dirty] widgets or for the whole
+    /// tree. It is never [Relayout::None].
+    pub relayout: Relayout,
+}
+
+impl<'a, 't> LayoutCtx<'a, 't> {
+    pub fn request_timer(&mut self, after: Duration) -> TimerToken {
+        self.timers.request_timer(after)
+    }
+}
+
+/// Lives next to the state of a child in a container and remembers the constraint the child was
+/// last laid out with. The result of the layout is kept in the state of the child itself, so a
+/// child that didn't ask for a relayout and gets the same constraint again can be skipped.
+#[derive(Copy, Clone, Debug)]
+pub struct LayoutCache {
+    constraint: Option<LayoutConstraint>,
+    dirty: bool,
+}
+
+impl Default for LayoutCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LayoutCache {
+    pub const fn new() -> Self {
+        LayoutCache {
+            constraint: None,
+            dirty: true,
+        }
+    }
+
+    /// Lays out `widget` unless its last layout is still valid.
+    pub fn layout<E, W: Widget<E>>(
+        &mut self,
+        widget: &mut W,
+        state: &mut W::State,
+        env: &mut E,
+        constraint: LayoutConstraint,
+        ctx: &mut LayoutCtx,
+    ) {
+        if self.dirty || ctx.relayout == Relayout::All || self.constraint != Some(constraint) {
+            widget.layout(state, env, constraint, ctx);
+
+            self.constraint = Some(constraint);
+            self.dirty = false;
+        }
+    }
+
+    /// Passes input on to the child with `f` and marks the child dirty if it asked for a relayout.
+    pub fn track<R>(&mut self, ctx: &mut InputCtx, f: impl FnOnce(&mut InputCtx) -> R) -> R {
+        let outer = std::mem::replace(&mut *ctx.relayout, Relayout::None);
+
+        let ret = f(ctx);
+
+        if *ctx.relayout != Relayout::None {
+            self.dirty = true;
+        }
+
+        *ctx.relayout = (*ctx.relayout).max(outer);
+        ret
+    }
 }
 
 #[non_exhaustive]
@@ -119,6 +362,13 @@ pub trait WidgetState {
     fn extra_layers(&self) -> u8 {
         0
     }
+
+    /// The distance from the top of the [min_size](WidgetState::min_size) to the baseline of the
+    /// first line of text in the widget, if it has any text. Rows use this to line up text across
+    /// their children.
+    fn baseline(&self) -> Option<f64> {
+        None
+    }
 }
 
 pub trait Widget<E> {
@@ -173,6 +423,7 @@ pub trait Widget<E> {
         input_state: &InputState,
         theme: &Theme,
         focus: bool,
+        ctx: &mut InputCtx,
     ) -> InputReturn {
         Default::default()
     }
@@ -188,286 +439,331 @@ pub trait Widget<E> {
         input_state: &InputState,
         theme: &Theme,
         focus: bool,
+        ctx: &mut InputCtx,
     ) {}
-}
-
-pub struct WindowHandler<W: Widget<Runtime>> {
-    widget: W,
-    state: W::State,
-    input_state: InputState,
-    size: Size,
-    handle: Option<WindowHandle>,
-    theme: Theme,
-}
 
-impl<W: Widget<Runtime>> WindowHandler<W> {
-    fn rect(&self) -> Rect {
-        Rect::from_origin_size((0., 0.), self.size)
+    /// Whether this widget or anything inside it can take the keyboard focus. This must not have
+    /// side effects since containers use it to figure out where to move the focus to.
+    fn accepts_focus(&mut self, state: &mut Self::State, env: &mut E) -> bool {
+        false
     }
-}
 
-fn druid_shell_mouse_button_to_mouse_button(
-    mouse_button: druid_shell::MouseButton,
-) -> Option<MouseButton> {
-    use druid_shell::MouseButton as Db;
+    /// Moves the keyboard focus to the next or previous focusable widget inside this one. `focus`
+    /// tells whether the focus is currently inside this widget. If it isn't, the focus enters at
+    /// the start (or at the end for [FocusDirection::Previous]).
+    ///
+    /// Returns `false` if the focus wrapped past the end (or start), in which case the parent has to
+    /// move it on to its next child.
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        false
+    }
 
-    match mouse_button {
-        Db::None => None,
-        Db::Left => Some(MouseButton::Primary),
-        Db::Right => Some(MouseButton::Secondary),
-        Db::Middle => Some(MouseButton::Middle),
-        Db::X1 => Some(MouseButton::X1),
-        Db::X2 => Some(MouseButton::X2),
+    /// Called when a timer fires, with the token [InputCtx::request_timer] returned for it. Timers
+    /// aren't addressed to a particular widget, so containers have to pass this on to all of their
+    /// children and widgets have to check whether the token is one of theirs.
+    fn handle_timer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
     }
 }
 
-impl<W: Widget<Runtime>> WinHandler for WindowHandler<W>
-where
-    Self: 'static,
-{
-    fn connect(&mut self, handle: &WindowHandle) {
-        self.handle = Some(handle.clone());
-    }
+/// The color windows are cleared with unless an [AppLauncher] sets another one.
+pub(crate) const DEFAULT_BACKGROUND_COLOR: theme::Color = 0x00_00_00_FF;
 
-    fn prepare_paint(&mut self) {
-        self.handle.as_ref().unwrap().invalidate();
+/// Lays out the root widget as far as `relayout` asks for and renders all of its layers on top of
+/// a cleared background.
+pub(crate) fn paint_root<E, W: Widget<E>>(
+    widget: &mut W,
+    state: &mut W::State,
+    env: &mut E,
+    size: Size,
+    piet: &mut Piet,
+    theme: &Theme,
+    background_color: theme::Color,
+    input_state: &InputState,
+    timers: &mut dyn Timers,
+    relayout: Relayout,
+) {
+    layout_root(widget, state, env, size, piet.text(), theme, timers, relayout);
+
+    // a fill instead of a clear so this stays inside the clip of a partial repaint
+    piet.fill(
+        Rect::from_origin_size((0.0, 0.0), size),
+        &Color::Rgba32(background_color),
+    );
+
+    for i in 0..1 + state.extra_layers() {
+        widget.render(
+            state,
+            env,
+            Rect::from_origin_size((0.0, 0.0), size),
+            i,
+            true,
+            &mut RenderCtx {
+                piet,
+                theme,
+                input_state,
+            },
+        );
     }
+}
 
-    fn paint(&mut self, piet: &mut Piet, _: &Region) {
-        let mut ctx = LayoutCtx {
-            text: piet.text(),
-            theme: &self.theme,
-        };
+/// Lays out the root widget with the window size unless `relayout` is [Relayout::None].
+pub(crate) fn layout_root<E, W: Widget<E>>(
+    widget: &mut W,
+    state: &mut W::State,
+    env: &mut E,
+    size: Size,
+    text: &mut PietText,
+    theme: &Theme,
+    timers: &mut dyn Timers,
+    relayout: Relayout,
+) {
+    if relayout == Relayout::None {
+        return;
+    }
 
-        let state = &mut self.state;
+    widget.layout(
+        state,
+        env,
+        LayoutConstraint::tight(size),
+        &mut LayoutCtx {
+            text,
+            theme,
+            timers,
+            relayout,
+        },
+    );
+}
 
-        self.widget.layout(
+/// Finds the layer hit by `pos` and passes the input on to the root widget.
+pub(crate) fn dispatch_cursor_input<E, W: Widget<E>>(
+    widget: &mut W,
+    state: &mut W::State,
+    env: &mut E,
+    size: Size,
+    pos: Point,
+    input: CursorInput,
+    input_state: &InputState,
+    theme: &Theme,
+    ctx: &mut InputCtx,
+) -> InputReturn {
+    let rect = Rect::from_origin_size((0., 0.), size);
+
+    let layer = widget.test_input_pos_layer(state, env, rect, pos);
+
+    if let Some(layer) = layer {
+        let ret = widget.handle_cursor_input(
             state,
-            &mut Runtime {},
-            LayoutConstraint::new(Some(self.size.width), Some(self.size.height)),
-            &mut ctx,
+            env,
+            rect,
+            pos,
+            layer,
+            input,
+            input_state,
+            theme,
+            true,
+            ctx,
         );
 
-        piet.clear(Color::Rgba32(0x00_00_00_FF));
-
-        for i in 0..1 + state.extra_layers() {
-            self.widget.render(
-                state,
-                &mut Runtime {},
-                Rect::from_origin_size((0.0, 0.0), self.size),
-                i,
-                true,
-                &mut RenderCtx {
-                    piet,
-                    theme: &self.theme,
-                    input_state: &self.input_state,
-                },
-            );
+        // the widget that had the focus before has to be repainted as well
+        if ret.demand_focus {
+            ctx.invalidate_window();
         }
+
+        ret
+    } else {
+        Default::default()
     }
+}
 
-    fn size(&mut self, size: Size) {
-        self.size = size;
+fn focus_direction(input: &KeyboardInput) -> Option<FocusDirection> {
+    match input {
+        KeyboardInput::KeyDown(event) => {
+            if HotKey::new(None, KbKey::Tab).matches(event) {
+                Some(FocusDirection::Next)
+            } else if HotKey::new(SysMods::Shift, KbKey::Tab).matches(event) {
+                Some(FocusDirection::Previous)
+            } else {
+                None
+            }
+        }
+        _ => None,
     }
+}
 
-    fn mouse_move(&mut self, event: &MouseEvent) {
-        let state = &mut self.state;
+/// Passes keyboard input on to the root widget. Tab and Shift+Tab are handled here by moving the
+/// focus, wrapping around at the end.
+pub(crate) fn dispatch_keyboard_input<E, W: Widget<E>>(
+    widget: &mut W,
+    state: &mut W::State,
+    env: &mut E,
+    size: Size,
+    input: &KeyboardInput,
+    input_state: &InputState,
+    theme: &Theme,
+    ctx: &mut InputCtx,
+) {
+    if let Some(direction) = focus_direction(input) {
+        if !widget.move_focus(state, env, direction, true, ctx) {
+            widget.move_focus(state, env, direction, false, ctx);
+        }
 
-        self.input_state.cursor_pos = Some(event.pos);
-        self.input_state.mods = event.mods;
-        let rect = Rect::from_origin_size((0., 0.), self.size);
+        // the focus ring moves, but nothing changes size
+        ctx.invalidate_window();
+        return;
+    }
 
-        let layer = self.widget.test_input_pos_layer(state, &mut Runtime {}, rect, event.pos);
+    widget.handle_keyboard_input(
+        state,
+        env,
+        Rect::from_origin_size((0., 0.), size),
+        input,
+        input_state,
+        theme,
+        true,
+        ctx,
+    );
+}
 
-        if let Some(layer) = layer {
-            self.widget.handle_cursor_input(
-                state,
-                &mut Runtime {},
-                rect,
-                event.pos,
-                layer,
-                CursorInput::Move,
-                &self.input_state,
-                &self.theme,
-                true,
-            );
-        }
+/// Passes a fired timer on to the root widget.
+pub(crate) fn dispatch_timer<E, W: Widget<E>>(
+    widget: &mut W,
+    state: &mut W::State,
+    env: &mut E,
+    token: TimerToken,
+    ctx: &mut InputCtx,
+) {
+    widget.handle_timer(state, env, token, true, ctx);
+}
 
-        self.handle.as_ref().unwrap().request_anim_frame();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestHarness;
+    use crate::widgets::button::text_button;
+    use crate::widgets::lists::row;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct CountLayouts(Rc<Cell<u32>>);
+
+    impl Widget<Runtime> for CountLayouts {
+        type State = widgets::NoneWidgetState;
+
+        fn layout(
+            &mut self,
+            _: &mut Self::State,
+            _: &mut Runtime,
+            _: LayoutConstraint,
+            _: &mut LayoutCtx,
+        ) {
+            self.0.set(self.0.get() + 1);
+        }
     }
 
-    fn mouse_leave(&mut self) {
-        self.input_state.cursor_pos = None;
-        self.handle.as_ref().unwrap().request_anim_frame();
+    #[test]
+    fn moving_the_focus_only_repaints() {
+        let layouts = Rc::new(Cell::new(0));
+        let mut harness = TestHarness::new(
+            row(flex_content![
+                CountLayouts(layouts.clone()),
+                text_button("A", |_: &mut Runtime| ()),
+                text_button("B", |_: &mut Runtime| ()),
+            ]),
+            (200., 100.),
+        );
+        harness.render();
+
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        assert!(!harness.damage().rects().is_empty());
+        assert_eq!(layouts.get(), 1);
     }
 
-    fn mouse_down(&mut self, event: &MouseEvent) {
-        let state = &mut self.state;
+    #[test]
+    fn tab_between_buttons() {
+        let mut harness = TestHarness::with_env(
+            row(flex_content![
+                text_button("A", |clicks: &mut [u32; 2]| clicks[0] += 1),
+                text_button("B", |clicks: &mut [u32; 2]| clicks[1] += 1),
+            ]),
+            [0, 0],
+            (200., 100.),
+        );
 
-        self.input_state.mouse_down = true;
-        self.input_state.mods = event.mods;
-        if let Some(button) = druid_shell_mouse_button_to_mouse_button(event.button) {
-            let rect = Rect::from_origin_size((0., 0.), self.size);
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Enter));
+        assert_eq!(*harness.env(), [0, 1]);
 
-            let layer = self.widget.test_input_pos_layer(state, &mut Runtime {}, rect, event.pos);
+        // wraps around to the first button
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Enter));
+        assert_eq!(*harness.env(), [1, 1]);
 
-            if let Some(layer) = layer {
-                self.widget.handle_cursor_input(
-                    state,
-                    &mut Runtime {},
-                    rect,
-                    event.pos,
-                    layer,
-                    CursorInput::Down(button),
-                    &self.input_state,
-                    &self.theme,
-                    true,
-                );
-            }
-        }
-        self.handle.as_ref().unwrap().request_anim_frame();
-    }
-
-    fn mouse_up(&mut self, event: &MouseEvent) {
-        let state = &mut self.state;
-
-        self.input_state.mouse_down = false;
-        self.input_state.mods = event.mods;
-        if let Some(button) = druid_shell_mouse_button_to_mouse_button(event.button) {
-            let rect = Rect::from_origin_size((0., 0.), self.size);
-
-            let layer = self.widget.test_input_pos_layer(state, &mut Runtime {}, rect, event.pos);
-
-            if let Some(layer) = layer {
-                self.widget.handle_cursor_input(
-                    state,
-                    &mut Runtime {},
-                    rect,
-                    event.pos,
-                    layer,
-                    CursorInput::Up(button),
-                    &self.input_state,
-                    &self.theme,
-                    true,
-                );
-            }
-        }
-        self.handle.as_ref().unwrap().request_anim_frame();
+        harness.key_down(KeyEvent::for_test(Modifiers::SHIFT, KbKey::Tab));
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Enter));
+        assert_eq!(*harness.env(), [1, 2]);
     }
 
-    fn key_down(&mut self, event: KeyEvent) -> bool {
-        let state = &mut self.state;
-
-        self.input_state.mods = event.mods;
-        self.widget.handle_keyboard_input(
-            state,
-            &mut Runtime {},
-            Rect::from_origin_size((0., 0.), self.size),
-            &KeyboardInput::KeyDown(event),
-            &self.input_state,
-            &self.theme,
-            true,
+    #[test]
+    fn hover_only_damages_the_hovered_button() {
+        let mut harness = TestHarness::new(
+            row(flex_content![
+                text_button("A", |_: &mut Runtime| ()),
+                text_button("B", |_: &mut Runtime| ()),
+            ]),
+            (200., 100.),
         );
+        harness.render();
 
-        self.handle.as_ref().unwrap().request_anim_frame();
+        harness.move_to((10., 10.));
+        assert!(!harness.damage().is_empty());
+        assert!(harness.damage().bounding_box().x1 <= 100.);
 
-        true
-    }
+        // moving within the same button changes nothing
+        harness.render();
+        harness.move_to((20., 10.));
+        assert!(harness.damage().is_empty());
 
-    fn as_any(&mut self) -> &mut dyn core::any::Any {
-        self
+        harness.click((20., 10.));
+        assert_eq!(
+            harness.damage().bounding_box(),
+            Rect::from_origin_size((0., 0.), (200., 100.))
+        );
     }
 
-    fn request_close(&mut self) {
-        self.handle.as_ref().unwrap().close();
-    }
-}
+    #[test]
+    fn only_env_changes_relayout() {
+        let layouts = Rc::new(Cell::new(0));
+        let mut harness = TestHarness::new(
+            row(flex_content![
+                CountLayouts(layouts.clone()),
+                text_button("B", |_: &mut Runtime| ()),
+            ]),
+            (200., 100.),
+        );
+        assert_eq!(layouts.get(), 1);
 
-#[non_exhaustive]
-pub struct Runtime {}
+        harness.move_to((150., 10.));
+        harness.render();
+        assert_eq!(layouts.get(), 1);
 
-pub fn run<W: Widget<Runtime> + 'static>(
-    title: &str,
-    widget: W,
-) {
-    let app = Application::new().unwrap();
-    let mut builder = WindowBuilder::new(app.clone());
-    builder.set_title(title);
-
-    let rect_theme = RectTheme {
-        background_color: (0x00_77_FF_FF, 0x00_77_FF_FF),
-        foreground_color: (0xFF_FF_FF_FF, 0xFF_FF_FF_FF),
-
-        // shape: RectShape::Square,
-        border_color: (0x00_00_00_FF, 0x00_00_00_FF),
-        border_width: 1,
-        padding: 16,
-        margin: 4,
-    };
-
-    let text_theme = TextTheme {
-        font: "Arial",
-        size: 16,
-    };
-
-    builder.set_handler(Box::new(WindowHandler {
-        widget,
-        state: W::State::new(),
-        input_state: Default::default(),
-        size: Size::new(0., 0.),
-        handle: None,
-        theme: Theme {
-            rect: WidgetTheme {
-                enabled: WidgetVariants {
-                    normal: rect_theme,
-                    active: RectTheme {
-                        background_color: (0x33_AA_FF_FF, 0x33_AA_FF_FF),
-                        ..rect_theme
-                    },
-                    danger: RectTheme {
-                        background_color: (0xFF_77_00_FF, 0xFF_77_00_FF),
-                        ..rect_theme
-                    },
-                },
-                disabled: WidgetVariants {
-                    normal: rect_theme,
-                    active: rect_theme,
-                    danger: rect_theme,
-                },
-            },
-            rect_outline: WidgetTheme {
-                enabled: WidgetVariants {
-                    normal: rect_theme,
-                    active: RectTheme {
-                        background_color: (0x33_AA_FF_FF, 0x33_AA_FF_FF),
-                        ..rect_theme
-                    },
-                    danger: RectTheme {
-                        background_color: (0xFF_77_00_FF, 0xFF_77_00_FF),
-                        ..rect_theme
-                    },
-                },
-                disabled: WidgetVariants {
-                    normal: rect_theme,
-                    active: rect_theme,
-                    danger: rect_theme,
-                },
-            },
-            text: WidgetTheme {
-                enabled: WidgetVariants {
-                    normal: text_theme,
-                    active: text_theme,
-                    danger: text_theme,
-                },
-                disabled: WidgetVariants {
-                    normal: text_theme,
-                    active: text_theme,
-                    danger: text_theme,
-                },
-            },
-        },
-    }));
-    let window = builder.build().unwrap();
-    window.show();
-    app.run(None);
+        // the click runs a callback that might have changed the environment
+        harness.click((150., 10.));
+        assert_eq!(layouts.get(), 2);
+    }
 }
@@ -1,6 +1,6 @@
 use crate::*;
 
-pub enum Pass<'la, 'lb, 'ra, 'rb, 'rc, 't, 'is, 'ka, T> {
+pub enum Pass<'la, 'lb, 'ra, 'rb, 'rc, 't, 'is, 'ka, 'ia, 'ib, T> {
     Layout {
         ctx: &'la mut LayoutCtx<'lb, 't>,
         constraint: LayoutConstraint,
@@ -35,6 +35,7 @@ pub enum Pass<'la, 'lb, 'ra, 'rb, 'rc, 't, 'is, 'ka, T> {
         input_state: &'is InputState,
         theme: &'t Theme,
         focus: bool,
+        ctx: &'ia mut InputCtx<'ib>,
 
         ret: fn(InputReturn) -> T,
     },
@@ -44,6 +45,24 @@ pub enum Pass<'la, 'lb, 'ra, 'rb, 'rc, 't, 'is, 'ka, T> {
         input_state: &'is InputState,
         theme: &'t Theme,
         focus: bool,
+        ctx: &'ia mut InputCtx<'ib>,
+
+        ret: fn() -> T,
+    },
+    AcceptsFocus {
+        ret: fn(bool) -> T,
+    },
+    MoveFocus {
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &'ia mut InputCtx<'ib>,
+
+        ret: fn(bool) -> T,
+    },
+    Timer {
+        token: TimerToken,
+        focus: bool,
+        ctx: &'ia mut InputCtx<'ib>,
 
         ret: fn() -> T,
     },
@@ -129,6 +148,7 @@ impl<E, W: PassWidget<E>> Widget<E> for PassWidgetWidget<W> {
         input_state: &InputState,
         theme: &Theme,
         focus: bool,
+        ctx: &mut InputCtx,
     ) -> InputReturn {
         self.0.pass(
             state,
@@ -141,6 +161,7 @@ impl<E, W: PassWidget<E>> Widget<E> for PassWidgetWidget<W> {
                 input_state,
                 theme,
                 focus,
+                ctx,
                 ret: |r| r,
             },
         )
@@ -155,6 +176,7 @@ impl<E, W: PassWidget<E>> Widget<E> for PassWidgetWidget<W> {
         input_state: &InputState,
         theme: &Theme,
         focus: bool,
+        ctx: &mut InputCtx,
     ) {
         self.0.pass(
             state,
@@ -165,6 +187,51 @@ impl<E, W: PassWidget<E>> Widget<E> for PassWidgetWidget<W> {
                 input_state,
                 theme,
                 focus,
+                ctx,
+                ret: || (),
+            },
+        )
+    }
+
+    fn accepts_focus(&mut self, state: &mut W::State, env: &mut E) -> bool {
+        self.0.pass(state, env, Pass::AcceptsFocus { ret: |r| r })
+    }
+
+    fn move_focus(
+        &mut self,
+        state: &mut W::State,
+        env: &mut E,
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        self.0.pass(
+            state,
+            env,
+            Pass::MoveFocus {
+                direction,
+                focus,
+                ctx,
+                ret: |r| r,
+            },
+        )
+    }
+
+    fn handle_timer(
+        &mut self,
+        state: &mut W::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        self.0.pass(
+            state,
+            env,
+            Pass::Timer {
+                token,
+                focus,
+                ctx,
                 ret: || (),
             },
         )
@@ -214,6 +281,7 @@ impl<E, W: Widget<E>> PassWidget<E> for WidgetPassWidget<W> {
                 input_state,
                 theme,
                 focus,
+                ctx,
                 ret,
             } => ret(self.0.handle_cursor_input(
                 state,
@@ -225,6 +293,7 @@ impl<E, W: Widget<E>> PassWidget<E> for WidgetPassWidget<W> {
                 input_state,
                 theme,
                 focus,
+                ctx,
             )),
 
             KeyboardInput {
@@ -233,10 +302,38 @@ impl<E, W: Widget<E>> PassWidget<E> for WidgetPassWidget<W> {
                 input_state,
                 theme,
                 focus,
+                ctx,
+                ret,
+            } => {
+                self.0.handle_keyboard_input(
+                    state,
+                    env,
+                    rect,
+                    input,
+                    input_state,
+                    theme,
+                    focus,
+                    ctx,
+                );
+                ret()
+            }
+
+            AcceptsFocus { ret } => ret(self.0.accepts_focus(state, env)),
+
+            MoveFocus {
+                direction,
+                focus,
+                ctx,
+                ret,
+            } => ret(self.0.move_focus(state, env, direction, focus, ctx)),
+
+            Timer {
+                token,
+                focus,
+                ctx,
                 ret,
             } => {
-                self.0
-                    .handle_keyboard_input(state, env, rect, input, input_state, theme, focus);
+                self.0.handle_timer(state, env, token, focus, ctx);
                 ret()
             }
         }
@@ -0,0 +1,747 @@
+//! Running widgets in windows.
+//!
+//! All windows of an application share one [Runtime], which is the environment their root widgets
+//! get. Through it widgets can reach the application-wide environment and open or close windows.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+use crate::theme::ThemeError;
+use crate::*;
+
+/// How often a watched theme file is checked for changes.
+const THEME_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Identifies a window opened by a [Runtime].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct WindowId(u64);
+
+impl WindowId {
+    fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+
+        WindowId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// The environment of the root widgets of all windows.
+///
+/// `E` is the application-level environment shared between all windows. Opening and closing
+/// windows only takes effect once the current event has been handled.
+#[non_exhaustive]
+pub struct Runtime<E = ()> {
+    env: E,
+    window: WindowId,
+    windows: HashMap<WindowId, WindowHandle>,
+    requests: Vec<WindowRequest<E>>,
+    /// Counts the events that might have changed the environment, so windows can tell whether
+    /// another window did that since their last layout.
+    env_generation: u64,
+}
+
+enum WindowRequest<E> {
+    Open {
+        id: WindowId,
+        title: String,
+        root: Box<dyn Root<Runtime<E>>>,
+    },
+    Close(WindowId),
+}
+
+impl<E> Runtime<E> {
+    pub(crate) fn new(env: E) -> Self {
+        Runtime {
+            env,
+            window: WindowId::next(),
+            windows: HashMap::new(),
+            requests: Vec::new(),
+            env_generation: 0,
+        }
+    }
+
+    pub fn env(&self) -> &E {
+        &self.env
+    }
+
+    pub fn env_mut(&mut self) -> &mut E {
+        &mut self.env
+    }
+
+    /// The window the event that is currently being handled belongs to.
+    pub fn window(&self) -> WindowId {
+        self.window
+    }
+
+    /// Opens a new window showing `widget`. The widget gets its own state, but shares this
+    /// runtime with all other windows.
+    pub fn open_window<W: Widget<Runtime<E>> + 'static>(
+        &mut self,
+        title: &str,
+        widget: W,
+    ) -> WindowId
+    where
+        E: 'static,
+    {
+        let id = WindowId::next();
+
+        self.requests.push(WindowRequest::Open {
+            id,
+            title: title.to_owned(),
+            root: Box::new(RootWidget::new(widget)),
+        });
+
+        id
+    }
+
+    /// Closes the window with the given id. The application quits when its last window closes.
+    pub fn close_window(&mut self, id: WindowId) {
+        self.requests.push(WindowRequest::Close(id));
+    }
+}
+
+/// A root widget together with its state. This erases the type of the widget so windows showing
+/// different widgets can live side by side.
+trait Root<E> {
+    fn paint(
+        &mut self,
+        env: &mut E,
+        size: Size,
+        piet: &mut Piet,
+        theme: &Theme,
+        background_color: theme::Color,
+        input_state: &InputState,
+        timers: &mut dyn Timers,
+        relayout: Relayout,
+    );
+
+    fn cursor_input(
+        &mut self,
+        env: &mut E,
+        size: Size,
+        pos: Point,
+        input: CursorInput,
+        input_state: &InputState,
+        theme: &Theme,
+        ctx: &mut InputCtx,
+    );
+
+    fn keyboard_input(
+        &mut self,
+        env: &mut E,
+        size: Size,
+        input: &KeyboardInput,
+        input_state: &InputState,
+        theme: &Theme,
+        ctx: &mut InputCtx,
+    );
+
+    fn timer(&mut self, env: &mut E, token: TimerToken, ctx: &mut InputCtx);
+}
+
+struct RootWidget<W: Widget<E>, E> {
+    widget: W,
+    state: W::State,
+}
+
+impl<E, W: Widget<E>> RootWidget<W, E> {
+    fn new(widget: W) -> Self {
+        RootWidget {
+            widget,
+            state: W::State::new(),
+        }
+    }
+}
+
+impl<E, W: Widget<E>> Root<E> for RootWidget<W, E> {
+    fn paint(
+        &mut self,
+        env: &mut E,
+        size: Size,
+        piet: &mut Piet,
+        theme: &Theme,
+        background_color: theme::Color,
+        input_state: &InputState,
+        timers: &mut dyn Timers,
+        relayout: Relayout,
+    ) {
+        paint_root(
+            &mut self.widget,
+            &mut self.state,
+            env,
+            size,
+            piet,
+            theme,
+            background_color,
+            input_state,
+            timers,
+            relayout,
+        );
+    }
+
+    fn cursor_input(
+        &mut self,
+        env: &mut E,
+        size: Size,
+        pos: Point,
+        input: CursorInput,
+        input_state: &InputState,
+        theme: &Theme,
+        ctx: &mut InputCtx,
+    ) {
+        dispatch_cursor_input(
+            &mut self.widget,
+            &mut self.state,
+            env,
+            size,
+            pos,
+            input,
+            input_state,
+            theme,
+            ctx,
+        );
+    }
+
+    fn keyboard_input(
+        &mut self,
+        env: &mut E,
+        size: Size,
+        input: &KeyboardInput,
+        input_state: &InputState,
+        theme: &Theme,
+        ctx: &mut InputCtx,
+    ) {
+        dispatch_keyboard_input(
+            &mut self.widget,
+            &mut self.state,
+            env,
+            size,
+            input,
+            input_state,
+            theme,
+            ctx,
+        );
+    }
+
+    fn timer(&mut self, env: &mut E, token: TimerToken, ctx: &mut InputCtx) {
+        dispatch_timer(&mut self.widget, &mut self.state, env, token, ctx);
+    }
+}
+
+pub struct WindowHandler<E> {
+    id: WindowId,
+    root: Box<dyn Root<Runtime<E>>>,
+    runtime: Rc<RefCell<Runtime<E>>>,
+    input_state: InputState,
+    size: Size,
+    handle: Option<WindowHandle>,
+    theme: Theme,
+    background_color: theme::Color,
+    /// The parts of the window the event that is being handled changed.
+    damage: Region,
+    /// How much has to be laid out again before the next paint.
+    relayout: Relayout,
+    /// The [Runtime::env_generation] of the last layout.
+    env_generation: u64,
+    theme_watch: Option<ThemeWatch>,
+}
+
+/// A theme file the theme of a window is reloaded from when it changes.
+struct ThemeWatch {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    timer: TimerToken,
+    /// Gets the errors of loading the changed file, see [AppLauncher::on_theme_error].
+    on_error: Option<Rc<dyn Fn(ThemeError)>>,
+}
+
+impl ThemeWatch {
+    fn new(path: PathBuf, on_error: Option<Rc<dyn Fn(ThemeError)>>) -> Self {
+        ThemeWatch {
+            modified: modified_time(&path),
+            path,
+            timer: TimerToken::INVALID,
+            on_error,
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn druid_shell_mouse_button_to_mouse_button(
+    mouse_button: druid_shell::MouseButton,
+) -> Option<MouseButton> {
+    use druid_shell::MouseButton as Db;
+
+    match mouse_button {
+        Db::None => None,
+        Db::Left => Some(MouseButton::Primary),
+        Db::Right => Some(MouseButton::Secondary),
+        Db::Middle => Some(MouseButton::Middle),
+        Db::X1 => Some(MouseButton::X1),
+        Db::X2 => Some(MouseButton::X2),
+    }
+}
+
+/// How a window looks when it opens.
+#[derive(Clone)]
+struct WindowConfig {
+    size: Option<Size>,
+    min_size: Option<Size>,
+    resizable: bool,
+    theme: Theme,
+    /// The file the theme is reloaded from whenever it changes.
+    theme_file: Option<PathBuf>,
+    on_theme_error: Option<Rc<dyn Fn(ThemeError)>>,
+    background_color: theme::Color,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            size: None,
+            min_size: None,
+            resizable: true,
+            theme: Default::default(),
+            theme_file: None,
+            on_theme_error: None,
+            background_color: DEFAULT_BACKGROUND_COLOR,
+        }
+    }
+}
+
+fn build_window<E: 'static>(
+    runtime: &Rc<RefCell<Runtime<E>>>,
+    id: WindowId,
+    title: &str,
+    root: Box<dyn Root<Runtime<E>>>,
+    config: WindowConfig,
+) -> WindowHandle {
+    let mut builder = WindowBuilder::new(Application::global());
+    builder.set_title(title);
+    builder.resizable(config.resizable);
+
+    if let Some(size) = config.size {
+        builder.set_size(size);
+    }
+
+    if let Some(min_size) = config.min_size {
+        builder.set_min_size(min_size);
+    }
+
+    builder.set_handler(Box::new(WindowHandler {
+        id,
+        root,
+        runtime: runtime.clone(),
+        input_state: Default::default(),
+        size: Size::new(0., 0.),
+        handle: None,
+        theme: config.theme,
+        background_color: config.background_color,
+        damage: Region::EMPTY,
+        relayout: Relayout::All,
+        env_generation: 0,
+        theme_watch: config
+            .theme_file
+            .map(|path| ThemeWatch::new(path, config.on_theme_error)),
+    }));
+
+    let handle = builder.build().unwrap();
+    runtime.borrow_mut().windows.insert(id, handle.clone());
+    handle
+}
+
+impl<E: 'static> WindowHandler<E> {
+    fn rect(&self) -> Rect {
+        Rect::from_origin_size((0., 0.), self.size)
+    }
+
+    /// Calls `f` with the runtime and then opens and closes the windows it asked for. The runtime
+    /// must not be borrowed while doing that since other windows might get events right away.
+    fn with_runtime<R>(&mut self, f: impl FnOnce(&mut Self, &mut Runtime<E>) -> R) -> R {
+        let runtime = self.runtime.clone();
+
+        let (ret, requests) = {
+            let mut runtime = runtime.borrow_mut();
+            runtime.window = self.id;
+
+            let ret = f(self, &mut runtime);
+            (ret, std::mem::take(&mut runtime.requests))
+        };
+
+        for request in requests {
+            match request {
+                WindowRequest::Open { id, title, root } => {
+                    // new windows look like the one that opened them
+                    let config = WindowConfig {
+                        theme: self.theme.clone(),
+                        theme_file: self.theme_watch.as_ref().map(|watch| watch.path.clone()),
+                        on_theme_error: self
+                            .theme_watch
+                            .as_ref()
+                            .and_then(|watch| watch.on_error.clone()),
+                        background_color: self.background_color,
+                        ..Default::default()
+                    };
+
+                    build_window(&runtime, id, &title, root, config).show();
+                }
+                WindowRequest::Close(id) => {
+                    let handle = runtime.borrow().windows.get(&id).cloned();
+
+                    if let Some(handle) = handle {
+                        handle.close();
+                    }
+                }
+            }
+        }
+
+        ret
+    }
+
+    fn cursor_input(&mut self, pos: Point, input: CursorInput) {
+        self.with_runtime(|this, runtime| {
+            this.root.cursor_input(
+                runtime,
+                this.size,
+                pos,
+                input,
+                &this.input_state,
+                &this.theme,
+                &mut InputCtx {
+                    clipboard: &mut SystemClipboard,
+                    timers: this.handle.as_mut().unwrap(),
+                    damage: &mut this.damage,
+                    relayout: &mut this.relayout,
+                    wheel_handled: false,
+                },
+            )
+        });
+
+        self.finish_event();
+    }
+
+    /// Loads the watched theme file again if it changed since the last time. If it can't be
+    /// loaded the old theme is kept, since an editor might just be in the middle of saving it.
+    fn reload_theme(&mut self) -> Result<(), ThemeError> {
+        let handle = self.handle.as_mut().unwrap();
+        let watch = self.theme_watch.as_mut().unwrap();
+        watch.timer = handle.request_timer(THEME_POLL_INTERVAL);
+
+        let modified = modified_time(&watch.path);
+        if modified == watch.modified {
+            return Ok(());
+        }
+        watch.modified = modified;
+
+        self.theme = Theme::load(&watch.path)?;
+        self.relayout = Relayout::All;
+        handle.invalidate();
+
+        Ok(())
+    }
+
+    /// Asks for a repaint of the parts of the window that the last event changed. If it might have
+    /// changed the environment the other windows are repainted as well.
+    fn finish_event(&mut self) {
+        self.damage.intersect_with(self.rect());
+
+        let handle = self.handle.as_ref().unwrap();
+        for rect in self.damage.rects() {
+            handle.invalidate_rect(*rect);
+        }
+
+        self.damage.clear();
+
+        if self.relayout == Relayout::All {
+            let mut runtime = self.runtime.borrow_mut();
+            runtime.env_generation += 1;
+            self.env_generation = runtime.env_generation;
+
+            for (id, handle) in &runtime.windows {
+                if *id != self.id {
+                    handle.invalidate();
+                }
+            }
+        }
+    }
+}
+
+impl<E: 'static> WinHandler for WindowHandler<E> {
+    fn connect(&mut self, handle: &WindowHandle) {
+        self.handle = Some(handle.clone());
+
+        if let Some(watch) = &mut self.theme_watch {
+            watch.timer = handle.request_timer(THEME_POLL_INTERVAL);
+        }
+    }
+
+    /// Nothing to do here, the damage of the handled events was already reported to the window.
+    fn prepare_paint(&mut self) {}
+
+    fn paint(&mut self, piet: &mut Piet, invalid: &Region) {
+        piet.save().unwrap();
+        piet.clip(invalid.to_bez_path());
+
+        self.with_runtime(|this, runtime| {
+            if this.env_generation != runtime.env_generation {
+                this.env_generation = runtime.env_generation;
+                this.relayout = Relayout::All;
+            }
+
+            this.root.paint(
+                runtime,
+                this.size,
+                piet,
+                &this.theme,
+                this.background_color,
+                &this.input_state,
+                this.handle.as_mut().unwrap(),
+                this.relayout,
+            );
+
+            this.relayout = Relayout::None;
+        });
+
+        piet.restore().unwrap();
+    }
+
+    fn size(&mut self, size: Size) {
+        self.size = size;
+        self.relayout = Relayout::All;
+
+        if let Some(handle) = &self.handle {
+            handle.invalidate();
+        }
+    }
+
+    fn mouse_move(&mut self, event: &MouseEvent) {
+        self.input_state.cursor_pos = Some(event.pos);
+        self.input_state.mods = event.mods;
+
+        self.cursor_input(event.pos, CursorInput::Move);
+    }
+
+    fn wheel(&mut self, event: &MouseEvent) {
+        self.input_state.mods = event.mods;
+
+        self.cursor_input(event.pos, CursorInput::Wheel(event.wheel_delta));
+    }
+
+    /// Widgets don't find out about this, so everything that might have been hovered is repainted.
+    fn mouse_leave(&mut self) {
+        self.input_state.cursor_pos = None;
+        self.handle.as_ref().unwrap().invalidate();
+    }
+
+    fn mouse_down(&mut self, event: &MouseEvent) {
+        self.input_state.mouse_down = true;
+        self.input_state.mods = event.mods;
+        if let Some(button) = druid_shell_mouse_button_to_mouse_button(event.button) {
+            self.cursor_input(event.pos, CursorInput::Down(button));
+        }
+    }
+
+    fn mouse_up(&mut self, event: &MouseEvent) {
+        self.input_state.mouse_down = false;
+        self.input_state.mods = event.mods;
+        if let Some(button) = druid_shell_mouse_button_to_mouse_button(event.button) {
+            self.cursor_input(event.pos, CursorInput::Up(button));
+        }
+    }
+
+    fn key_down(&mut self, event: KeyEvent) -> bool {
+        // druid-shell doesn't tell us about compositions yet, but at least the keys that are part
+        // of one must not be handled as regular input.
+        if event.is_composing {
+            return false;
+        }
+
+        self.input_state.mods = event.mods;
+        self.with_runtime(|this, runtime| {
+            this.root.keyboard_input(
+                runtime,
+                this.size,
+                &KeyboardInput::KeyDown(event),
+                &this.input_state,
+                &this.theme,
+                &mut InputCtx {
+                    clipboard: &mut SystemClipboard,
+                    timers: this.handle.as_mut().unwrap(),
+                    damage: &mut this.damage,
+                    relayout: &mut this.relayout,
+                    wheel_handled: false,
+                },
+            )
+        });
+
+        self.finish_event();
+
+        true
+    }
+
+    fn timer(&mut self, token: TimerToken) {
+        if self.theme_watch.as_ref().map_or(false, |watch| watch.timer == token) {
+            if let Err(error) = self.reload_theme() {
+                if let Some(on_error) = &self.theme_watch.as_ref().unwrap().on_error {
+                    on_error(error);
+                }
+            }
+            return;
+        }
+
+        self.with_runtime(|this, runtime| {
+            this.root.timer(
+                runtime,
+                token,
+                &mut InputCtx {
+                    clipboard: &mut SystemClipboard,
+                    timers: this.handle.as_mut().unwrap(),
+                    damage: &mut this.damage,
+                    relayout: &mut this.relayout,
+                    wheel_handled: false,
+                },
+            )
+        });
+
+        self.finish_event();
+    }
+
+    fn as_any(&mut self) -> &mut dyn core::any::Any {
+        self
+    }
+
+    fn request_close(&mut self) {
+        self.handle.as_ref().unwrap().close();
+    }
+
+    fn destroy(&mut self) {
+        let last = {
+            let mut runtime = self.runtime.borrow_mut();
+            runtime.windows.remove(&self.id);
+            runtime.windows.is_empty()
+        };
+
+        if last {
+            Application::global().quit();
+        }
+    }
+}
+
+/// Configures the application and its first window before running it.
+///
+/// Setting a window icon isn't supported since druid-shell doesn't offer that yet.
+pub struct AppLauncher<E = ()> {
+    title: String,
+    config: WindowConfig,
+    env: E,
+}
+
+impl AppLauncher {
+    pub fn new(title: &str) -> Self {
+        AppLauncher {
+            title: title.to_owned(),
+            config: Default::default(),
+            env: (),
+        }
+    }
+}
+
+impl<E: 'static> AppLauncher<E> {
+    /// Sets the application-level environment all windows share through the [Runtime].
+    pub fn with_env<F: 'static>(self, env: F) -> AppLauncher<F> {
+        AppLauncher {
+            title: self.title,
+            config: self.config,
+            env,
+        }
+    }
+
+    pub fn with_size(mut self, size: impl Into<Size>) -> Self {
+        self.config.size = Some(size.into());
+        self
+    }
+
+    pub fn with_min_size(mut self, min_size: impl Into<Size>) -> Self {
+        self.config.min_size = Some(min_size.into());
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.config.resizable = resizable;
+        self
+    }
+
+    /// The theme of the first window. Windows opened later get the theme of the window that
+    /// opened them.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.config.theme = theme;
+        self
+    }
+
+    /// Like [AppLauncher::with_theme], but loads the theme from a TOML or RON file. See
+    /// [Theme::load].
+    pub fn with_theme_file(mut self, path: impl AsRef<Path>) -> Result<Self, ThemeError> {
+        self.config.theme = Theme::load(path)?;
+        Ok(self)
+    }
+
+    /// Like [AppLauncher::with_theme_file], but the windows keep watching the file and switch to
+    /// the new theme whenever it changes. A changed file that can't be loaded is passed to the
+    /// [AppLauncher::on_theme_error] handler and the previous theme is kept.
+    pub fn watch_theme_file(mut self, path: impl Into<PathBuf>) -> Result<Self, ThemeError> {
+        let path = path.into();
+        self.config.theme = Theme::load(&path)?;
+        self.config.theme_file = Some(path);
+        Ok(self)
+    }
+
+    /// Gets the errors of reloading a theme file set with [AppLauncher::watch_theme_file], for
+    /// example to show them to whoever is editing the theme. Without a handler they are ignored.
+    pub fn on_theme_error(mut self, handler: impl Fn(ThemeError) + 'static) -> Self {
+        self.config.on_theme_error = Some(Rc::new(handler));
+        self
+    }
+
+    /// The color the window is cleared with before the widgets are rendered.
+    pub fn with_background_color(mut self, color: theme::Color) -> Self {
+        self.config.background_color = color;
+        self
+    }
+
+    /// Opens the first window showing `widget` and runs the application until the last window
+    /// is closed.
+    pub fn run<W: Widget<Runtime<E>> + 'static>(self, widget: W) {
+        let app = Application::new().unwrap();
+        let runtime = Rc::new(RefCell::new(Runtime::new(self.env)));
+
+        let id = runtime.borrow().window;
+        build_window(
+            &runtime,
+            id,
+            &self.title,
+            Box::new(RootWidget::new(widget)),
+            self.config,
+        )
+        .show();
+
+        app.run(None);
+    }
+}
+
+pub fn run<W: Widget<Runtime> + 'static>(title: &str, widget: W) {
+    AppLauncher::new(title).run(widget)
+}
+
+/// Like [run], but with an application-level environment that all windows share through the
+/// [Runtime].
+pub fn run_with_env<E: 'static, W: Widget<Runtime<E>> + 'static>(title: &str, widget: W, env: E) {
+    AppLauncher::new(title).with_env(env).run(widget)
+}
@@ -0,0 +1,392 @@
+//! Driving a widget tree without opening a window.
+//!
+//! The [TestHarness] owns a widget together with its state and runs the same layout, render and
+//! input dispatch the [WindowHandler] does, except that rendering happens into an offscreen bitmap.
+//! Instead of the system clipboard it uses a [MemoryClipboard], and timers only fire when the test
+//! calls [TestHarness::advance_time].
+//!
+//! Outside of egrikor's own tests the module is only built with the `testing` feature, which adds
+//! the `png` dependency needed by the [snapshot] comparisons.
+
+use std::time::Duration;
+
+use druid_shell::kurbo::{Point, Size, Vec2};
+use druid_shell::{KeyEvent, TimerToken};
+use piet_common::{Device, ImageFormat, Piet, RenderContext};
+
+use crate::text::MemoryClipboard;
+use crate::*;
+
+pub mod snapshot;
+
+/// The pixels of a rendered frame in row-major RGBA order with separate (not premultiplied) alpha.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Frame {
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * self.width + x) * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+}
+
+/// Timers on a fake clock that only moves forward when the harness is told to.
+#[derive(Default)]
+struct FakeTimers {
+    now: Duration,
+    pending: Vec<(Duration, TimerToken)>,
+}
+
+impl Timers for FakeTimers {
+    fn request_timer(&mut self, after: Duration) -> TimerToken {
+        let token = TimerToken::next();
+        self.pending.push((self.now + after, token));
+        token
+    }
+}
+
+impl FakeTimers {
+    /// Removes and returns the earliest timer that is due by `deadline`.
+    fn pop_due(&mut self, deadline: Duration) -> Option<(Duration, TimerToken)> {
+        let (i, _) = self
+            .pending
+            .iter()
+            .enumerate()
+            .filter(|(_, (due, _))| *due <= deadline)
+            .min_by_key(|(_, (due, _))| *due)?;
+
+        Some(self.pending.remove(i))
+    }
+}
+
+pub struct TestHarness<W: Widget<E>, E = Runtime> {
+    widget: W,
+    state: W::State,
+    env: E,
+    input_state: InputState,
+    theme: Theme,
+    window_size: Size,
+    clipboard: MemoryClipboard,
+    timers: FakeTimers,
+    damage: Region,
+    relayout: Relayout,
+}
+
+impl<W: Widget<Runtime>> TestHarness<W> {
+    pub fn new(widget: W, window_size: impl Into<Size>) -> Self {
+        Self::with_env(widget, Runtime::new(()), window_size)
+    }
+}
+
+impl<E, W: Widget<E>> TestHarness<W, E> {
+    /// Like [TestHarness::new], but for widgets that need an environment other than [Runtime].
+    pub fn with_env(widget: W, env: E, window_size: impl Into<Size>) -> Self {
+        let mut harness = TestHarness {
+            widget,
+            state: W::State::new(),
+            env,
+            input_state: Default::default(),
+            theme: Default::default(),
+            window_size: window_size.into(),
+            clipboard: Default::default(),
+            timers: Default::default(),
+            damage: Region::EMPTY,
+            relayout: Relayout::All,
+        };
+
+        harness.update_layout();
+        harness
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self.layout();
+        self
+    }
+
+    pub fn env(&self) -> &E {
+        &self.env
+    }
+
+    /// Everything is laid out again after the environment was changed through this.
+    pub fn env_mut(&mut self) -> &mut E {
+        self.relayout = Relayout::All;
+        &mut self.env
+    }
+
+    pub fn state(&self) -> &W::State {
+        &self.state
+    }
+
+    pub fn input_state(&self) -> &InputState {
+        &self.input_state
+    }
+
+    pub fn clipboard(&self) -> &MemoryClipboard {
+        &self.clipboard
+    }
+
+    pub fn clipboard_mut(&mut self) -> &mut MemoryClipboard {
+        &mut self.clipboard
+    }
+
+    /// The number of timers that were requested and haven't fired yet.
+    pub fn pending_timers(&self) -> usize {
+        self.timers.pending.len()
+    }
+
+    pub fn window_size(&self) -> Size {
+        self.window_size
+    }
+
+    /// The size the root widget reported in the last layout.
+    pub fn size(&self) -> Size {
+        self.state.min_size()
+    }
+
+    pub fn resize(&mut self, window_size: impl Into<Size>) {
+        self.window_size = window_size.into();
+        self.layout();
+    }
+
+    /// The parts of the window that the input since the last [render](TestHarness::render) asked to
+    /// be repainted.
+    pub fn damage(&self) -> &Region {
+        &self.damage
+    }
+
+    /// Runs a layout pass on the whole tree.
+    pub fn layout(&mut self) {
+        self.relayout = Relayout::All;
+        self.update_layout();
+    }
+
+    /// Lays out as much as the input since the last layout asked for. The input methods do this
+    /// on their own after each event, just like a window does when it repaints.
+    fn update_layout(&mut self) {
+        self.damage
+            .intersect_with(Rect::from_origin_size((0., 0.), self.window_size));
+
+        let mut device = Device::new().unwrap();
+        let mut target = device.bitmap_target(1, 1, 1.0).unwrap();
+        let mut piet = target.render_context();
+
+        layout_root(
+            &mut self.widget,
+            &mut self.state,
+            &mut self.env,
+            self.window_size,
+            piet.text(),
+            &self.theme,
+            &mut self.timers,
+            self.relayout,
+        );
+        self.relayout = Relayout::None;
+
+        piet.finish().unwrap();
+    }
+
+    /// Lays out and renders all layers of the widget into an offscreen bitmap of the window size.
+    pub fn render(&mut self) -> Frame {
+        let width = self.window_size.width.ceil() as usize;
+        let height = self.window_size.height.ceil() as usize;
+
+        let mut device = Device::new().unwrap();
+        let mut target = device.bitmap_target(width, height, 1.0).unwrap();
+
+        {
+            let mut piet = target.render_context();
+            self.paint(&mut piet);
+            piet.finish().unwrap();
+        }
+
+        self.damage.clear();
+
+        // the cairo backend can only hand out premultiplied pixels
+        let mut pixels = vec![0; width * height * 4];
+        target
+            .copy_raw_pixels(ImageFormat::RgbaPremul, &mut pixels)
+            .unwrap();
+
+        for pixel in pixels.chunks_exact_mut(4) {
+            let alpha = pixel[3] as u16;
+
+            if alpha != 0 {
+                for channel in &mut pixel[..3] {
+                    *channel = ((*channel as u16 * 255 + alpha / 2) / alpha).min(255) as u8;
+                }
+            }
+        }
+
+        Frame {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn paint(&mut self, piet: &mut Piet) {
+        paint_root(
+            &mut self.widget,
+            &mut self.state,
+            &mut self.env,
+            self.window_size,
+            piet,
+            &self.theme,
+            DEFAULT_BACKGROUND_COLOR,
+            &self.input_state,
+            &mut self.timers,
+            self.relayout,
+        );
+        self.relayout = Relayout::None;
+    }
+
+    fn cursor_input(&mut self, pos: Point, input: CursorInput) -> InputReturn {
+        let ret = dispatch_cursor_input(
+            &mut self.widget,
+            &mut self.state,
+            &mut self.env,
+            self.window_size,
+            pos,
+            input,
+            &self.input_state,
+            &self.theme,
+            &mut InputCtx {
+                clipboard: &mut self.clipboard,
+                timers: &mut self.timers,
+                damage: &mut self.damage,
+                relayout: &mut self.relayout,
+                wheel_handled: false,
+            },
+        );
+
+        self.update_layout();
+        ret
+    }
+
+    pub fn move_to(&mut self, pos: impl Into<Point>) -> InputReturn {
+        let pos = pos.into();
+        self.input_state.cursor_pos = Some(pos);
+        self.cursor_input(pos, CursorInput::Move)
+    }
+
+    pub fn mouse_leave(&mut self) {
+        self.input_state.cursor_pos = None;
+    }
+
+    pub fn mouse_down(&mut self, pos: impl Into<Point>, button: MouseButton) -> InputReturn {
+        let pos = pos.into();
+        self.input_state.cursor_pos = Some(pos);
+        self.input_state.mouse_down = true;
+        self.cursor_input(pos, CursorInput::Down(button))
+    }
+
+    pub fn mouse_up(&mut self, pos: impl Into<Point>, button: MouseButton) -> InputReturn {
+        let pos = pos.into();
+        self.input_state.cursor_pos = Some(pos);
+        self.input_state.mouse_down = false;
+        self.cursor_input(pos, CursorInput::Up(button))
+    }
+
+    pub fn wheel(&mut self, pos: impl Into<Point>, delta: impl Into<Vec2>) -> InputReturn {
+        let pos = pos.into();
+        self.input_state.cursor_pos = Some(pos);
+        self.cursor_input(pos, CursorInput::Wheel(delta.into()))
+    }
+
+    /// Moves the cursor to `pos` and presses and releases the primary button there.
+    pub fn click(&mut self, pos: impl Into<Point>) {
+        let pos = pos.into();
+        self.move_to(pos);
+        self.mouse_down(pos, MouseButton::Primary);
+        self.mouse_up(pos, MouseButton::Primary);
+    }
+
+    pub fn key_down(&mut self, event: KeyEvent) {
+        self.input_state.mods = event.mods;
+        self.keyboard_input(KeyboardInput::KeyDown(event));
+    }
+
+    /// Sends any kind of keyboard input, like the composition events of an input method.
+    pub fn keyboard_input(&mut self, input: KeyboardInput) {
+        dispatch_keyboard_input(
+            &mut self.widget,
+            &mut self.state,
+            &mut self.env,
+            self.window_size,
+            &input,
+            &self.input_state,
+            &self.theme,
+            &mut InputCtx {
+                clipboard: &mut self.clipboard,
+                timers: &mut self.timers,
+                damage: &mut self.damage,
+                relayout: &mut self.relayout,
+                wheel_handled: false,
+            },
+        );
+
+        self.update_layout();
+    }
+
+    /// Moves the fake clock forward by `duration`, firing the timers that come due in order. Timers
+    /// requested while doing so fire as well if they are due before the end.
+    pub fn advance_time(&mut self, duration: Duration) {
+        let end = self.timers.now + duration;
+
+        while let Some((due, token)) = self.timers.pop_due(end) {
+            self.timers.now = due;
+
+            dispatch_timer(
+                &mut self.widget,
+                &mut self.state,
+                &mut self.env,
+                token,
+                &mut InputCtx {
+                    clipboard: &mut self.clipboard,
+                    timers: &mut self.timers,
+                    damage: &mut self.damage,
+                    relayout: &mut self.relayout,
+                    wheel_handled: false,
+                },
+            );
+        }
+
+        self.timers.now = end;
+        self.update_layout();
+    }
+
+    /// Sends a key down event for every character of `text`.
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.key_down(KeyEvent::for_test(
+                druid_shell::Modifiers::default(),
+                c.to_string().as_str(),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::button::text_button;
+
+    #[test]
+    fn render_fills_window() {
+        let mut harness = TestHarness::new(text_button("Click", |_: &mut Runtime| ()), (64., 32.));
+
+        let frame = harness.render();
+        assert_eq!((frame.width, frame.height), (64, 32));
+        assert_eq!(frame.pixels.len(), 64 * 32 * 4);
+    }
+}
@@ -0,0 +1,239 @@
+//! Comparing rendered frames against checked-in golden images.
+//!
+//! Golden images live in `tests/snapshots/<name>.png`. When a comparison fails the rendered frame is
+//! written next to it as `<name>.new.png` together with `<name>.diff.png`, which shows the
+//! mismatching pixels in red on top of a faded copy of the golden image. Setting the
+//! `EGRIKOR_UPDATE_SNAPSHOTS` environment variable overwrites the golden images instead.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use super::Frame;
+
+pub const DEFAULT_TOLERANCE: u8 = 2;
+
+pub const UPDATE_ENV_VAR: &str = "EGRIKOR_UPDATE_SNAPSHOTS";
+
+/// Renders a widget into a frame of the given size and compares it against a golden image. Widgets
+/// that need an environment other than [Runtime](crate::Runtime) get it with `env = ...`.
+///
+/// ```ignore
+/// assert_render_snapshot!(text_button("Ok", |_: &mut Runtime| ()), (100., 40.), "ok_button");
+/// assert_render_snapshot!(checkbox(true, |_: &mut Runtime| ()), (40., 40.), "checked", tolerance = 8);
+/// assert_render_snapshot!(textbox(|c: &mut TextBoxContent| c, |_| ()), env = TextBoxContent::new(), (120., 40.), "empty_textbox");
+/// ```
+#[macro_export]
+macro_rules! assert_render_snapshot {
+    (@frame $frame:expr, $name:expr, $tolerance:expr) => {{
+        $crate::testing::snapshot::assert_snapshot(
+            &$frame,
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots"),
+            $name,
+            $tolerance,
+        );
+    }};
+    ($widget:expr, env = $env:expr, $size:expr, $name:expr $(,)?) => {
+        $crate::assert_render_snapshot!(
+            $widget,
+            env = $env,
+            $size,
+            $name,
+            tolerance = $crate::testing::snapshot::DEFAULT_TOLERANCE
+        )
+    };
+    ($widget:expr, env = $env:expr, $size:expr, $name:expr, tolerance = $tolerance:expr $(,)?) => {
+        $crate::assert_render_snapshot!(
+            @frame $crate::testing::TestHarness::with_env($widget, $env, $size).render(),
+            $name,
+            $tolerance
+        )
+    };
+    ($widget:expr, $size:expr, $name:expr $(,)?) => {
+        $crate::assert_render_snapshot!(
+            $widget,
+            $size,
+            $name,
+            tolerance = $crate::testing::snapshot::DEFAULT_TOLERANCE
+        )
+    };
+    ($widget:expr, $size:expr, $name:expr, tolerance = $tolerance:expr $(,)?) => {
+        $crate::assert_render_snapshot!(
+            @frame $crate::testing::TestHarness::new($widget, $size).render(),
+            $name,
+            $tolerance
+        )
+    };
+}
+
+pub fn assert_snapshot(frame: &Frame, dir: impl AsRef<Path>, name: &str, tolerance: u8) {
+    let dir = dir.as_ref();
+    let golden_path = dir.join(format!("{}.png", name));
+    let new_path = dir.join(format!("{}.new.png", name));
+    let diff_path = dir.join(format!("{}.diff.png", name));
+
+    if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+        std::fs::create_dir_all(dir).unwrap();
+        write_png(frame, &golden_path);
+        return;
+    }
+
+    if !golden_path.exists() {
+        std::fs::create_dir_all(dir).unwrap();
+        write_png(frame, &new_path);
+        panic!(
+            "no golden image for snapshot `{}`, the rendered frame was written to {} (set {} to accept it)",
+            name,
+            new_path.display(),
+            UPDATE_ENV_VAR,
+        );
+    }
+
+    let golden = read_png(&golden_path);
+
+    if let Some(diff) = diff_frames(&golden, frame, tolerance) {
+        write_png(frame, &new_path);
+        write_png(&diff.image, &diff_path);
+        panic!(
+            "snapshot `{}` doesn't match: {} pixels differ by more than {}, see {} and {}",
+            name,
+            diff.differing_pixels,
+            tolerance,
+            new_path.display(),
+            diff_path.display(),
+        );
+    }
+
+    let _ = std::fs::remove_file(new_path);
+    let _ = std::fs::remove_file(diff_path);
+}
+
+pub struct FrameDiff {
+    pub differing_pixels: usize,
+    pub image: Frame,
+}
+
+/// Compares two frames channel by channel. Returns `None` if no channel of any pixel differs by
+/// more than `tolerance`.
+pub fn diff_frames(expected: &Frame, actual: &Frame, tolerance: u8) -> Option<FrameDiff> {
+    if expected.width != actual.width || expected.height != actual.height {
+        return Some(FrameDiff {
+            differing_pixels: expected.width.max(actual.width) * expected.height.max(actual.height),
+            image: actual.clone(),
+        });
+    }
+
+    let mut differing_pixels = 0;
+    let mut pixels = Vec::with_capacity(expected.pixels.len());
+
+    for (e, a) in expected
+        .pixels
+        .chunks_exact(4)
+        .zip(actual.pixels.chunks_exact(4))
+    {
+        let differs = e
+            .iter()
+            .zip(a)
+            .any(|(e, a)| (*e as i16 - *a as i16).unsigned_abs() > tolerance as u16);
+
+        if differs {
+            differing_pixels += 1;
+            pixels.extend_from_slice(&[0xFF, 0x00, 0x00, 0xFF]);
+        } else {
+            let luma = (e[0] as u16 + e[1] as u16 + e[2] as u16) / 3;
+            let faded = (luma / 4) as u8;
+            pixels.extend_from_slice(&[faded, faded, faded, 0xFF]);
+        }
+    }
+
+    if differing_pixels == 0 {
+        None
+    } else {
+        Some(FrameDiff {
+            differing_pixels,
+            image: Frame {
+                width: expected.width,
+                height: expected.height,
+                pixels,
+            },
+        })
+    }
+}
+
+pub fn write_png(frame: &Frame, path: impl AsRef<Path>) {
+    let file = File::create(path.as_ref()).unwrap();
+
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(file),
+        frame.width as u32,
+        frame.height as u32,
+    );
+    encoder.set_color(png::ColorType::RGBA);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&frame.pixels).unwrap();
+}
+
+pub fn read_png(path: impl AsRef<Path>) -> Frame {
+    let path: PathBuf = path.as_ref().into();
+    let decoder = png::Decoder::new(File::open(&path).unwrap());
+    let (info, mut reader) = decoder.read_info().unwrap();
+
+    assert!(
+        info.color_type == png::ColorType::RGBA && info.bit_depth == png::BitDepth::Eight,
+        "{} is not an 8-bit RGBA image",
+        path.display(),
+    );
+
+    let mut pixels = vec![0; info.buffer_size()];
+    reader.next_frame(&mut pixels).unwrap();
+
+    Frame {
+        width: info.width as usize,
+        height: info.height as usize,
+        pixels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: usize, height: usize, color: [u8; 4]) -> Frame {
+        Frame {
+            width,
+            height,
+            pixels: color.repeat(width * height),
+        }
+    }
+
+    #[test]
+    fn within_tolerance() {
+        let a = solid(4, 4, [0x10, 0x20, 0x30, 0xFF]);
+        let b = solid(4, 4, [0x12, 0x1E, 0x30, 0xFF]);
+
+        assert!(diff_frames(&a, &b, 2).is_none());
+        assert_eq!(diff_frames(&a, &b, 1).unwrap().differing_pixels, 16);
+    }
+
+    #[test]
+    fn diff_marks_pixels() {
+        let a = solid(2, 1, [0, 0, 0, 0xFF]);
+        let mut b = a.clone();
+        b.pixels[4] = 0xFF;
+
+        let diff = diff_frames(&a, &b, 0).unwrap();
+        assert_eq!(diff.differing_pixels, 1);
+        assert_eq!(diff.image.pixel(0, 0), [0, 0, 0, 0xFF]);
+        assert_eq!(diff.image.pixel(1, 0), [0xFF, 0, 0, 0xFF]);
+    }
+
+    #[test]
+    fn size_mismatch() {
+        let a = solid(2, 2, [0, 0, 0, 0xFF]);
+        let b = solid(3, 2, [0, 0, 0, 0xFF]);
+
+        assert!(diff_frames(&a, &b, 0).is_some());
+    }
+}
@@ -0,0 +1,47 @@
+//! Access to the clipboard for copy, cut and paste.
+
+use druid_shell::Application;
+
+/// A place to copy text to and paste it from.
+///
+/// Widgets get one through [`InputCtx`](crate::InputCtx), so tests can
+/// substitute a [`MemoryClipboard`] for the system clipboard.
+pub trait Clipboard {
+    /// Return the text currently on the clipboard, if there is any.
+    fn get_string(&mut self) -> Option<String>;
+
+    /// Put `text` on the clipboard, replacing whatever was there.
+    fn put_string(&mut self, text: &str);
+}
+
+/// The clipboard of the system, as provided by druid-shell.
+///
+/// This requires a running [`Application`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClipboard;
+
+impl Clipboard for SystemClipboard {
+    fn get_string(&mut self) -> Option<String> {
+        Application::global().clipboard().get_string()
+    }
+
+    fn put_string(&mut self, text: &str) {
+        Application::global().clipboard().put_string(text);
+    }
+}
+
+/// A clipboard that only lives in memory.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryClipboard {
+    pub contents: Option<String>,
+}
+
+impl Clipboard for MemoryClipboard {
+    fn get_string(&mut self) -> Option<String> {
+        self.contents.clone()
+    }
+
+    fn put_string(&mut self, text: &str) {
+        self.contents = Some(text.to_owned());
+    }
+}
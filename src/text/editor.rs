@@ -14,11 +14,13 @@
 
 //! A component for building text editing widgets
 
+use std::ops::Range;
+
 use super::{
-    movement, offset_for_delete_backwards, EditAction, EditableText, MouseAction, Movement,
-    Selection, TextLayout, TextStorage,
+    movement, offset_for_delete_backwards, Clipboard, EditAction, EditableText, MouseAction,
+    Movement, Selection, TextLayout, TextStorage,
 };
-use druid_shell::{Application, Modifiers};
+use druid_shell::Modifiers;
 use piet_common::PietText;
 use piet_common::{
     kurbo::{Line, Point, Rect},
@@ -39,6 +41,85 @@ pub struct Editor<T> {
     selection: Selection,
     multiline: bool,
     fixed_width: f64,
+    history: EditHistory,
+    /// Counts the changes to the text, to tell them apart from edits that only move the selection.
+    revision: u64,
+    composition: Option<Composition>,
+}
+
+/// Text an input method is composing.
+///
+/// The composed text lives in the buffer so it gets laid out like the rest,
+/// but it only enters the undo history once it is committed.
+#[derive(Debug, Clone)]
+struct Composition {
+    /// Where the composed text is in the buffer.
+    range: Range<usize>,
+    /// The text the composition replaced.
+    replaced: String,
+    /// The selection from before the composition started.
+    selection: Selection,
+}
+
+/// The undo and redo stacks of an [`Editor`].
+///
+/// Every edit is recorded as the text it replaced and the text it inserted
+/// at a given offset, which is enough to apply it in both directions.
+#[derive(Debug, Clone, Default)]
+struct EditHistory {
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    /// Whether the next insert may be merged into the last entry.
+    coalesce: bool,
+}
+
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    start: usize,
+    old: String,
+    new: String,
+    selection_before: Selection,
+    selection_after: Selection,
+}
+
+impl EditHistory {
+    fn record(
+        &mut self,
+        start: usize,
+        old: String,
+        new: String,
+        selection_before: Selection,
+        selection_after: Selection,
+        coalesce: bool,
+    ) {
+        self.redo.clear();
+
+        // consecutive typing is undone in one go
+        if coalesce && self.coalesce && old.is_empty() {
+            if let Some(last) = self.undo.last_mut() {
+                if last.start + last.new.len() == start {
+                    last.new.push_str(&new);
+                    last.selection_after = selection_after;
+                    return;
+                }
+            }
+        }
+
+        self.undo.push(HistoryEntry {
+            start,
+            old,
+            new,
+            selection_before,
+            selection_after,
+        });
+        self.coalesce = coalesce;
+    }
+
+    fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.coalesce = false;
+    }
 }
 
 impl<T: From<&'static str>> Editor<T> {
@@ -49,6 +130,9 @@ impl<T: From<&'static str>> Editor<T> {
             selection: Selection::caret(0),
             multiline: false,
             fixed_width: f64::INFINITY,
+            history: EditHistory::default(),
+            revision: 0,
+            composition: None,
         }
     }
 
@@ -90,9 +174,14 @@ impl<T: TextStorage + EditableText> Editor<T> {
     /// This must be set before the editor is used, such as in [`WidgetAdded`].
     ///
     /// [`WidgetAdded`]: ../enum.LifeCycle.html#variant.WidgetAdded
+    ///
+    /// This clears the undo history.
     pub fn set_text(&mut self, text: T) {
         self.selection = self.selection.constrained(&text);
         self.layout.set_text(text);
+        self.history.clear();
+        self.revision += 1;
+        self.composition = None;
     }
 
     /// Return the current selection.
@@ -121,28 +210,143 @@ impl<T: TextStorage + EditableText> Editor<T> {
 
     /// Handle a mouse click
     pub fn click(&mut self, pos: [f64; 2], mods: Modifiers) {
-        self.do_edit(EditAction::Click(self.mouse_action_for_event(pos, mods)));
+        self.history.coalesce = false;
+        self.mouse_click(self.mouse_action_for_event(pos, mods));
     }
 
     /// Handle a mouse drag
     pub fn drag(&mut self, pos: [f64; 2], mods: Modifiers) {
-        self.do_edit(EditAction::Drag(self.mouse_action_for_event(pos, mods)));
+        self.history.coalesce = false;
+        self.selection.end = self.mouse_action_for_event(pos, mods).column;
     }
 
     /// Handle a copy command
-    pub fn copy(&self, _data: &mut T) {
-        self.set_clipboard()
+    pub fn copy(&self, clipboard: &mut dyn Clipboard) {
+        self.set_clipboard(clipboard)
     }
 
     /// Handle a cut command
-    pub fn cut(&mut self, _data: &mut T) {
-        self.set_clipboard();
-        self.delete_backward();
+    pub fn cut(&mut self, clipboard: &mut dyn Clipboard) {
+        if !self.selection.is_caret() {
+            self.set_clipboard(clipboard);
+            self.delete_backward();
+        }
     }
 
     /// Handle a paste command
     pub fn paste(&mut self, t: String) {
-        self.do_edit(EditAction::Paste(t))
+        self.history.coalesce = false;
+        self.insert_text(&t, false);
+    }
+
+    /// Revert the last edit and restore the selection from before it.
+    pub fn undo(&mut self) {
+        self.history.coalesce = false;
+
+        if let Some(entry) = self.history.undo.pop() {
+            let range = entry.start..entry.start + entry.new.len();
+            self.layout.text.edit(range, entry.old.as_str());
+            self.revision += 1;
+            self.selection = entry.selection_before.constrained(&self.layout.text);
+            self.history.redo.push(entry);
+        }
+    }
+
+    /// Apply the last undone edit again.
+    pub fn redo(&mut self) {
+        self.history.coalesce = false;
+
+        if let Some(entry) = self.history.redo.pop() {
+            let range = entry.start..entry.start + entry.old.len();
+            self.layout.text.edit(range, entry.new.as_str());
+            self.revision += 1;
+            self.selection = entry.selection_after.constrained(&self.layout.text);
+            self.history.undo.push(entry);
+        }
+    }
+
+    /// Returns `true` if there is an edit that [`undo`](Editor::undo) would revert.
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty()
+    }
+
+    /// Returns `true` if there is an edit that [`redo`](Editor::redo) would apply.
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    /// Start composing text with an input method, replacing the selection.
+    ///
+    /// Does nothing if a composition is already in progress.
+    pub fn start_composition(&mut self) {
+        if self.composition.is_some() {
+            return;
+        }
+
+        self.history.coalesce = false;
+
+        let range = self.selection.range();
+        let replaced = self
+            .layout
+            .text
+            .slice(range.clone())
+            .map(|s| s.into_owned())
+            .unwrap_or_default();
+
+        self.layout.text.edit(range.clone(), "");
+        self.revision += 1;
+        self.composition = Some(Composition {
+            range: range.start..range.start,
+            replaced,
+            selection: self.selection,
+        });
+        self.selection = Selection::caret(range.start);
+    }
+
+    /// Replace the text being composed, starting a composition if there is
+    /// none yet.
+    pub fn update_composition(&mut self, text: &str) {
+        self.start_composition();
+
+        if let Some(composition) = &mut self.composition {
+            let start = composition.range.start;
+            self.layout.text.edit(composition.range.clone(), text);
+            self.revision += 1;
+            composition.range = start..start + text.len();
+            self.selection = Selection::caret(start + text.len());
+        }
+    }
+
+    /// End the composition and insert `text` in its place as a regular edit.
+    ///
+    /// An empty `text` cancels the composition and restores the text and
+    /// selection from before it.
+    pub fn commit_composition(&mut self, text: &str) {
+        if let Some(composition) = self.composition.take() {
+            self.layout
+                .text
+                .edit(composition.range, composition.replaced.as_str());
+            self.revision += 1;
+            self.selection = composition.selection.constrained(&self.layout.text);
+        }
+
+        if !text.is_empty() {
+            self.history.coalesce = false;
+            self.insert_text(text, false);
+        }
+    }
+
+    /// The range of the text that is being composed, if any.
+    pub fn composition_range(&self) -> Option<Range<usize>> {
+        self.composition.as_ref().map(|c| c.range.clone())
+    }
+
+    /// Returns the `Rect`s covering the text that is being composed.
+    pub fn composition_rects(&self) -> Vec<Rect> {
+        match self.composition_range() {
+            Some(range) if !range.is_empty() => self.layout.rects_for_range(range),
+            _ => Vec::new(),
+        }
     }
 
     /// Set the selection to the entire buffer.
@@ -150,6 +354,14 @@ impl<T: TextStorage + EditableText> Editor<T> {
         self.selection = Selection::new(0, self.layout.text.len());
     }
 
+    fn mouse_click(&mut self, action: MouseAction) {
+        if action.mods.shift() {
+            self.selection.end = action.column;
+        } else {
+            self.selection = Selection::caret(action.column);
+        }
+    }
+
     fn mouse_action_for_event(&self, pos: [f64; 2], mods: Modifiers) -> MouseAction {
         let pos = self
             .layout
@@ -186,13 +398,34 @@ impl<T: TextStorage + EditableText> Editor<T> {
     }
 
     /// Perform an [`EditAction`](enum.EditAction.html).
-    pub fn do_edit(&mut self, edit: EditAction) {
+    ///
+    /// `clipboard` is used by the copy, cut and paste actions. Returns whether
+    /// the text changed, as opposed to only the selection.
+    pub fn do_edit(&mut self, edit: EditAction, clipboard: &mut dyn Clipboard) -> bool {
+        let revision = self.revision;
+
         // if self.data_is_stale(data) {
         //     // log::warn!("editor data changed externally, skipping event {:?}", &edit);
         //     return;
         // }
+        if !matches!(edit, EditAction::Insert(_)) {
+            self.history.coalesce = false;
+        }
+
+        // any other edit keeps what has been composed so far
+        if let Some(range) = self.composition_range() {
+            let text = self
+                .layout
+                .text
+                .slice(range)
+                .map(|s| s.into_owned())
+                .unwrap_or_default();
+            self.commit_composition(&text);
+        }
+
         match edit {
-            EditAction::Insert(chars) | EditAction::Paste(chars) => self.insert(&chars),
+            EditAction::Insert(chars) => self.insert(&chars),
+            EditAction::Paste(chars) => self.paste(chars),
             EditAction::Backspace => self.delete_backward(),
             EditAction::Delete => self.delete_forward(),
             EditAction::JumpDelete(mvmt) | EditAction::JumpBackspace(mvmt) => {
@@ -201,8 +434,7 @@ impl<T: TextStorage + EditableText> Editor<T> {
                 } else {
                     self.selection
                 };
-                self.layout.text.edit(to_delete.range(), "");
-                self.selection = Selection::caret(to_delete.min());
+                self.replace(to_delete.range(), "", Selection::caret(to_delete.min()), false);
             }
             EditAction::Move(mvmt) => {
                 self.selection = movement(mvmt, self.selection, &self.layout, false)
@@ -210,16 +442,21 @@ impl<T: TextStorage + EditableText> Editor<T> {
             EditAction::ModifySelection(mvmt) => {
                 self.selection = movement(mvmt, self.selection, &self.layout, true)
             }
-            EditAction::Click(action) => {
-                if action.mods.shift() {
-                    self.selection.end = action.column;
-                } else {
-                    self.selection = Selection::caret(action.column);
-                }
-            }
+            EditAction::Click(action) => self.mouse_click(action),
             EditAction::Drag(action) => self.selection.end = action.column,
             EditAction::SelectAll => self.selection = Selection::new(0, self.layout.text.len()),
+            EditAction::Undo => self.undo(),
+            EditAction::Redo => self.redo(),
+            EditAction::Copy => self.copy(clipboard),
+            EditAction::Cut => self.cut(clipboard),
+            EditAction::PasteFromClipboard => {
+                if let Some(text) = clipboard.get_string() {
+                    self.paste(text);
+                }
+            }
         }
+
+        self.revision != revision
     }
 
     /// Draw this editor at the provided point.
@@ -241,6 +478,10 @@ impl<T: TextStorage + EditableText> Editor<T> {
     // }
 
     fn insert(&mut self, text: &str) {
+        self.insert_text(text, true);
+    }
+
+    fn insert_text(&mut self, text: &str, coalesce: bool) {
         // if we aren't multiline, we insert only up to the first newline
         let text = if self.multiline {
             text
@@ -248,23 +489,22 @@ impl<T: TextStorage + EditableText> Editor<T> {
             text.split('\n').next().unwrap_or("")
         };
         let sel = self.selection.range();
-        self.layout.text.edit(sel, text);
-        self.selection = Selection::caret(self.selection.min() + text.len());
+        let caret = Selection::caret(self.selection.min() + text.len());
+        self.replace(sel, text, caret, coalesce);
     }
 
     /// Delete backwards, using fancy logic when in caret mode.
     fn delete_backward(&mut self) {
-        let cursor_pos = if self.selection.is_caret() {
+        let to_delete = if self.selection.is_caret() {
             let del_end = self.selection.end;
             let del_start = offset_for_delete_backwards(&self.selection, &mut self.layout.text);
-            self.layout.text.edit(del_start..del_end, "");
-            del_start
+            del_start..del_end
         } else {
-            self.layout.text.edit(self.selection.range(), "");
-            self.selection.min()
+            self.selection.range()
         };
 
-        self.selection = Selection::caret(cursor_pos);
+        let caret = Selection::caret(to_delete.start);
+        self.replace(to_delete, "", caret, false);
     }
 
     fn delete_forward(&mut self) {
@@ -274,14 +514,41 @@ impl<T: TextStorage + EditableText> Editor<T> {
             self.selection
         };
 
-        self.layout.text.edit(to_delete.range(), "");
-        self.selection = Selection::caret(self.selection.min());
+        let caret = Selection::caret(self.selection.min());
+        self.replace(to_delete.range(), "", caret, false);
+    }
+
+    /// Replace `range` with `text`, set the selection and record the edit in
+    /// the undo history. `coalesce` allows merging the edit into the previous
+    /// one if both are plain inserts right after each other.
+    fn replace(&mut self, range: Range<usize>, text: &str, selection: Selection, coalesce: bool) {
+        let old = self
+            .layout
+            .text
+            .slice(range.clone())
+            .map(|s| s.into_owned())
+            .unwrap_or_default();
+
+        if !old.is_empty() || !text.is_empty() {
+            self.history.record(
+                range.start,
+                old,
+                text.to_owned(),
+                self.selection,
+                selection,
+                coalesce,
+            );
+            self.layout.text.edit(range, text);
+            self.revision += 1;
+        }
+
+        self.selection = selection;
     }
 
-    fn set_clipboard(&self) {
+    fn set_clipboard(&self, clipboard: &mut dyn Clipboard) {
         if let Some(text) = self.layout.text.slice(self.selection.range()) {
             if !text.is_empty() {
-                Application::global().clipboard().put_string(text);
+                clipboard.put_string(&text);
             }
         }
     }
@@ -296,6 +563,7 @@ impl<T: From<&'static str>> Default for Editor<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::text::MemoryClipboard;
 
     /// Devanagari codepoints are 3 utf-8 code units each.
     #[test]
@@ -333,4 +601,105 @@ mod tests {
 
         assert_eq!(editor.layout.text, String::from("\u{0073}\u{006F}"))
     }
+
+    #[test]
+    fn undo_coalesces_typing() {
+        let mut editor = Editor::<String>::new();
+        let mut clipboard = MemoryClipboard::default();
+
+        for c in "hello world".chars() {
+            editor.do_edit(EditAction::Insert(c.to_string()), &mut clipboard);
+        }
+        editor.do_edit(EditAction::Move(Movement::Left), &mut clipboard);
+        editor.do_edit(EditAction::Backspace, &mut clipboard);
+        assert_eq!(editor.layout.text, String::from("hello word"));
+
+        editor.do_edit(EditAction::Undo, &mut clipboard);
+        assert_eq!(editor.layout.text, String::from("hello world"));
+        assert_eq!(editor.selection.range(), 10..10);
+
+        editor.do_edit(EditAction::Undo, &mut clipboard);
+        assert_eq!(editor.layout.text, String::from(""));
+        assert!(!editor.can_undo());
+
+        editor.do_edit(EditAction::Redo, &mut clipboard);
+        assert_eq!(editor.layout.text, String::from("hello world"));
+        assert_eq!(editor.selection.range(), 11..11);
+
+        // a new edit drops whatever could have been redone
+        editor.do_edit(EditAction::Insert("!".into()), &mut clipboard);
+        assert!(!editor.can_redo());
+    }
+
+    #[test]
+    fn edits_report_text_changes() {
+        let mut editor = Editor::<String>::new();
+        let mut clipboard = MemoryClipboard::default();
+
+        assert!(editor.do_edit(EditAction::Insert("ab".into()), &mut clipboard));
+        assert!(!editor.do_edit(EditAction::Move(Movement::Left), &mut clipboard));
+        assert!(!editor.do_edit(EditAction::SelectAll, &mut clipboard));
+        assert!(!editor.do_edit(EditAction::Copy, &mut clipboard));
+        assert!(editor.do_edit(EditAction::Backspace, &mut clipboard));
+
+        // nothing left to delete
+        assert!(!editor.do_edit(EditAction::Backspace, &mut clipboard));
+    }
+
+    #[test]
+    fn undo_restores_selection() {
+        let mut editor = Editor::<String>::new();
+        let mut clipboard = MemoryClipboard::default();
+
+        editor.insert("abc");
+        editor.do_edit(EditAction::SelectAll, &mut clipboard);
+        editor.do_edit(EditAction::Insert("x".into()), &mut clipboard);
+        assert_eq!(editor.layout.text, String::from("x"));
+
+        editor.undo();
+        assert_eq!(editor.layout.text, String::from("abc"));
+        assert_eq!(editor.selection.range(), 0..3);
+    }
+
+    #[test]
+    fn composition_commits_as_one_edit() {
+        let mut editor = Editor::<String>::new();
+        editor.set_text(String::from("ab"));
+        editor.set_selection(Selection::caret(1));
+
+        editor.start_composition();
+        editor.update_composition("k");
+        editor.update_composition("か");
+        assert_eq!(editor.layout.text, String::from("aかb"));
+        assert_eq!(editor.composition_range(), Some(1..1 + "か".len()));
+        assert!(!editor.can_undo());
+
+        editor.commit_composition("下");
+        assert_eq!(editor.layout.text, String::from("a下b"));
+        assert_eq!(editor.composition_range(), None);
+
+        editor.undo();
+        assert_eq!(editor.layout.text, String::from("ab"));
+    }
+
+    #[test]
+    fn cut_and_paste() {
+        let mut editor = Editor::<String>::new();
+        let mut clipboard = MemoryClipboard::default();
+
+        editor.insert("hello world");
+        editor.set_selection(Selection::new(0, 6));
+        editor.do_edit(EditAction::Cut, &mut clipboard);
+        assert_eq!(editor.layout.text, String::from("world"));
+        assert_eq!(clipboard.contents.as_deref(), Some("hello "));
+
+        editor.do_edit(EditAction::Move(Movement::Right), &mut clipboard);
+        editor.do_edit(EditAction::PasteFromClipboard, &mut clipboard);
+        editor.do_edit(EditAction::PasteFromClipboard, &mut clipboard);
+        assert_eq!(editor.layout.text, String::from("whello hello orld"));
+
+        // each paste is undone on its own
+        editor.do_edit(EditAction::Undo, &mut clipboard);
+        assert_eq!(editor.layout.text, String::from("whello orld"));
+    }
 }
@@ -16,6 +16,7 @@
 
 // mod attribute;
 pub mod backspace;
+mod clipboard;
 mod editable_text;
 mod editor;
 mod font_descriptor;
@@ -28,6 +29,7 @@ mod text_input;
 
 // pub use self::attribute::{Attribute, AttributeSpans};
 pub use self::backspace::offset_for_delete_backwards;
+pub use self::clipboard::{Clipboard, MemoryClipboard, SystemClipboard};
 pub use self::editable_text::{EditableText, EditableTextCursor, StringCursor};
 pub use self::font_descriptor::FontDescriptor;
 pub use self::layout::{LayoutMetrics, TextLayout};
@@ -37,6 +37,12 @@ pub enum EditAction {
     JumpBackspace(Movement),
     Insert(String),
     Paste(String),
+    Undo,
+    Redo,
+    Copy,
+    Cut,
+    /// Insert whatever text is on the clipboard.
+    PasteFromClipboard,
 }
 
 /// Extra information related to mouse actions
@@ -94,6 +100,23 @@ impl TextInput for BasicTextInput {
             k_e if (HotKey::new(SysMods::Shift, KbKey::ArrowRight)).matches(k_e) => {
                 EditAction::ModifySelection(Movement::Right)
             }
+            // Undo (Ctrl+Z || Cmd+Z)
+            k_e if (HotKey::new(SysMods::Cmd, "z")).matches(k_e) => EditAction::Undo,
+            // Redo (Ctrl+Shift+Z || Cmd+Shift+Z || Ctrl+Y)
+            k_e if (HotKey::new(SysMods::CmdShift, "Z")).matches(k_e)
+                || (HotKey::new(SysMods::CmdShift, "z")).matches(k_e)
+                || (HotKey::new(SysMods::Cmd, "y")).matches(k_e) =>
+            {
+                EditAction::Redo
+            }
+            // Copy (Ctrl+C || Cmd+C)
+            k_e if (HotKey::new(SysMods::Cmd, "c")).matches(k_e) => EditAction::Copy,
+            // Cut (Ctrl+X || Cmd+X)
+            k_e if (HotKey::new(SysMods::Cmd, "x")).matches(k_e) => EditAction::Cut,
+            // Paste (Ctrl+V || Cmd+V)
+            k_e if (HotKey::new(SysMods::Cmd, "v")).matches(k_e) => {
+                EditAction::PasteFromClipboard
+            }
             // Select all (Ctrl+A || Cmd+A)
             k_e if (HotKey::new(SysMods::Cmd, "a")).matches(k_e) => EditAction::SelectAll,
             // Left word (Ctrl+ArrowLeft || Cmd+ArrowLeft)
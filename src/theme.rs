@@ -1,3 +1,13 @@
+//! The colors, fonts and shapes widgets are drawn with.
+//!
+//! A [Theme] can be built in code or loaded from a TOML or RON file with [Theme::load]. In those
+//! files colors are written as hex strings like `"#FF7700"` or `"#FF770080"`.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 /// The first byte is red, the second is green, the third is blue and the fourth is alpha.
 /// These are easier to define like `0xFF7700FF` than `[u8; 4]`.
 pub type Color = u32;
@@ -14,7 +24,7 @@ pub enum WidgetType {
     ProgressBar,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Theme {
     pub rect: WidgetTheme<RectTheme>,
 
@@ -24,13 +34,251 @@ pub struct Theme {
     pub text: WidgetTheme<TextTheme>,
 }
 
-#[derive(Copy, Clone, Debug)]
+impl Default for Theme {
+    fn default() -> Self {
+        let rect_theme = RectTheme {
+            background_color: (0x00_77_FF_FF, 0x33_92_FF_FF, 0x00_60_D0_FF),
+            foreground_color: (0xFF_FF_FF_FF, 0xFF_FF_FF_FF, 0xFF_FF_FF_FF),
+
+            shape: RectShape::Round(4),
+            border_color: (0x00_00_00_FF, 0x00_00_00_FF, 0x00_00_00_FF),
+            focus_color: 0xFF_FF_FF_FF,
+            border_width: 1,
+            padding: 16,
+            margin: 4,
+        };
+
+        let disabled_rect_theme = RectTheme {
+            background_color: (0x44_44_44_FF, 0x44_44_44_FF, 0x44_44_44_FF),
+            foreground_color: (0x88_88_88_FF, 0x88_88_88_FF, 0x88_88_88_FF),
+            ..rect_theme
+        };
+
+        let outline_theme = RectTheme {
+            background_color: (0x3A_3A_3A_FF, 0x3A_3A_3A_FF, 0x3A_3A_3A_FF),
+            shape: RectShape::Round(2),
+            border_color: (0x3A_3A_3A_FF, 0x4A_4A_4A_FF, 0x4A_4A_4A_FF),
+            focus_color: 0x00_8D_DD_FF,
+            padding: 4,
+            margin: 0,
+            ..rect_theme
+        };
+
+        let text_theme = TextTheme {
+            font: "Arial".to_owned(),
+            size: 16,
+            color: 0xFF_FF_FF_FF,
+            selection_color: 0xF3_00_21_FF,
+        };
+
+        let disabled_text_theme = TextTheme {
+            color: 0x88_88_88_FF,
+            ..text_theme.clone()
+        };
+
+        Theme {
+            rect: WidgetTheme {
+                enabled: WidgetVariants {
+                    normal: rect_theme,
+                    active: RectTheme {
+                        background_color: (0x33_AA_FF_FF, 0x5C_BB_FF_FF, 0x1A_8C_E0_FF),
+                        ..rect_theme
+                    },
+                    danger: RectTheme {
+                        background_color: (0xFF_77_00_FF, 0xFF_92_33_FF, 0xD0_60_00_FF),
+                        ..rect_theme
+                    },
+                },
+                disabled: WidgetVariants {
+                    normal: disabled_rect_theme,
+                    active: disabled_rect_theme,
+                    danger: disabled_rect_theme,
+                },
+            },
+            rect_outline: WidgetTheme {
+                enabled: WidgetVariants {
+                    normal: outline_theme,
+                    active: RectTheme {
+                        border_color: (0x33_AA_FF_FF, 0x33_AA_FF_FF, 0x33_AA_FF_FF),
+                        ..outline_theme
+                    },
+                    danger: RectTheme {
+                        border_color: (0xFF_77_00_FF, 0xFF_77_00_FF, 0xFF_77_00_FF),
+                        ..outline_theme
+                    },
+                },
+                disabled: WidgetVariants {
+                    normal: RectTheme {
+                        background_color: (0x2A_2A_2A_FF, 0x2A_2A_2A_FF, 0x2A_2A_2A_FF),
+                        foreground_color: (0x88_88_88_FF, 0x88_88_88_FF, 0x88_88_88_FF),
+                        ..outline_theme
+                    },
+                    active: RectTheme {
+                        background_color: (0x2A_2A_2A_FF, 0x2A_2A_2A_FF, 0x2A_2A_2A_FF),
+                        foreground_color: (0x88_88_88_FF, 0x88_88_88_FF, 0x88_88_88_FF),
+                        ..outline_theme
+                    },
+                    danger: RectTheme {
+                        background_color: (0x2A_2A_2A_FF, 0x2A_2A_2A_FF, 0x2A_2A_2A_FF),
+                        foreground_color: (0x88_88_88_FF, 0x88_88_88_FF, 0x88_88_88_FF),
+                        ..outline_theme
+                    },
+                },
+            },
+            text: WidgetTheme {
+                enabled: WidgetVariants {
+                    normal: text_theme.clone(),
+                    active: text_theme.clone(),
+                    danger: text_theme,
+                },
+                disabled: WidgetVariants {
+                    normal: disabled_text_theme.clone(),
+                    active: disabled_text_theme.clone(),
+                    danger: disabled_text_theme,
+                },
+            },
+        }
+    }
+}
+
+impl Theme {
+    /// This theme with the disabled look in place of the enabled one. Widgets that don't know
+    /// they are disabled are drawn with it to look disabled anyway.
+    pub fn to_disabled(&self) -> Theme {
+        Theme {
+            rect: self.rect.to_disabled(),
+            rect_outline: self.rect_outline.to_disabled(),
+            text: self.text.to_disabled(),
+        }
+    }
+
+    /// Loads a theme from a file. Files ending in `.ron` are read as RON and everything else as
+    /// TOML.
+    pub fn load(path: impl AsRef<Path>) -> Result<Theme, ThemeError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .map_err(|error| ThemeError::Io(path.to_owned(), error))?;
+
+        if path.extension().map_or(false, |extension| extension == "ron") {
+            Theme::from_ron(&source)
+        } else {
+            Theme::from_toml(&source)
+        }
+    }
+
+    pub fn from_toml(source: &str) -> Result<Theme, ThemeError> {
+        toml::from_str(source).map_err(|error| ThemeError::Parse(error.to_string()))
+    }
+
+    pub fn from_ron(source: &str) -> Result<Theme, ThemeError> {
+        ron::from_str(source).map_err(|error| ThemeError::Parse(error.to_string()))
+    }
+
+    pub fn to_toml(&self) -> String {
+        // everything in a theme can be represented in TOML
+        toml::to_string_pretty(self).unwrap()
+    }
+
+    pub fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, Default::default()).unwrap()
+    }
+}
+
+/// Why a [Theme] couldn't be loaded.
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(PathBuf, std::io::Error),
+    /// The file isn't valid TOML or RON or doesn't describe a theme.
+    Parse(String),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ThemeError::Io(path, error) => write!(f, "can't read {}: {}", path.display(), error),
+            ThemeError::Parse(error) => write!(f, "invalid theme: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ThemeError::Io(_, error) => Some(error),
+            ThemeError::Parse(_) => None,
+        }
+    }
+}
+
+/// (De)serializes a [Color] as a hex string like `"#RRGGBBAA"`. The alpha can be left out when
+/// reading, in which case the color is opaque.
+mod hex_color {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("#{:08X}", color))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        parse(&string).ok_or_else(|| {
+            serde::de::Error::custom(format!("invalid color {:?}, expected #RRGGBB[AA]", string))
+        })
+    }
+
+    pub fn parse(string: &str) -> Option<Color> {
+        let hex = string.strip_prefix('#')?;
+
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let color = u32::from_str_radix(hex, 16).ok()?;
+
+        match hex.len() {
+            6 => Some(color << 8 | 0xFF),
+            8 => Some(color),
+            _ => None,
+        }
+    }
+
+    /// The same for the `(normal, hovered, pressed)` triples of a [RectTheme].
+    pub mod triple {
+        use super::*;
+
+        #[derive(Serialize, Deserialize)]
+        #[serde(transparent)]
+        struct Hex(#[serde(with = "super")] Color);
+
+        pub fn serialize<S: Serializer>(
+            colors: &(Color, Color, Color),
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            (Hex(colors.0), Hex(colors.1), Hex(colors.2)).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<(Color, Color, Color), D::Error> {
+            let (normal, hovered, pressed) = <(Hex, Hex, Hex)>::deserialize(deserializer)?;
+            Ok((normal.0, hovered.0, pressed.0))
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct TextTheme {
-    pub font: &'static str,
+    /// The name of the font family. The system font is used when it isn't installed.
+    pub font: String,
     pub size: u16,
+    #[serde(with = "hex_color")]
+    pub color: Color,
+    /// The background of selected text.
+    #[serde(with = "hex_color")]
+    pub selection_color: Color,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct WidgetVariants<T> {
     pub normal: T,
     pub active: T,
@@ -42,20 +290,27 @@ pub struct WidgetVariants<T> {
 //     pub disabled: bool,
 // }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct WidgetTheme<T> {
     pub enabled: WidgetVariants<T>,
     pub disabled: WidgetVariants<T>,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum WidgetVariant {
     Normal,
     Active,
     Danger,
 }
 
-impl<T: Copy> WidgetTheme<T> {
+impl<T: Clone> WidgetTheme<T> {
+    fn to_disabled(&self) -> WidgetTheme<T> {
+        WidgetTheme {
+            enabled: self.disabled.clone(),
+            disabled: self.disabled.clone(),
+        }
+    }
+
     pub fn get(&self, variant: WidgetVariant, enabled: bool) -> T {
         let variant_themes = if enabled {
             &self.enabled
@@ -64,9 +319,9 @@ impl<T: Copy> WidgetTheme<T> {
         };
 
         match variant {
-            WidgetVariant::Normal => variant_themes.normal,
-            WidgetVariant::Active => variant_themes.active,
-            WidgetVariant::Danger => variant_themes.danger,
+            WidgetVariant::Normal => variant_themes.normal.clone(),
+            WidgetVariant::Active => variant_themes.active.clone(),
+            WidgetVariant::Danger => variant_themes.danger.clone(),
         }
     }
 }
@@ -79,26 +334,47 @@ pub struct BorderTheme {
     pub margin: u64,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum RectShape {
     Square,
+    /// With corners rounded by the given radius.
     Round(u64),
 }
 
-#[derive(Copy, Clone, Debug)]
+/// The colors are given as `(normal, hovered, pressed)`.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct RectTheme {
-    pub background_color: (Color, Color),
-    pub foreground_color: (Color, Color),
-    // pub shape: RectShape,
-    pub border_color: (Color, Color),
+    #[serde(with = "hex_color::triple")]
+    pub background_color: (Color, Color, Color),
+    /// For what is drawn on top of the background, like a checkmark.
+    #[serde(with = "hex_color::triple")]
+    pub foreground_color: (Color, Color, Color),
+    pub shape: RectShape,
+    #[serde(with = "hex_color::triple")]
+    pub border_color: (Color, Color, Color),
+    /// The color of the ring around a widget that has the keyboard focus.
+    #[serde(with = "hex_color")]
+    pub focus_color: Color,
     pub border_width: u64,
+    /// The space between the border and the content.
     pub padding: u64,
 
-    // not sure about this
+    /// The space around the border.
     pub margin: u64,
 }
 
+/// Picks the color for the state a widget is in from one of the color tuples of a [RectTheme].
+pub fn state_color(colors: (Color, Color, Color), hovered: bool, pressed: bool) -> Color {
+    match (hovered, pressed) {
+        (true, true) => colors.2,
+        (true, false) => colors.1,
+        (false, _) => colors.0,
+    }
+}
+
 impl RectTheme {
+    /// How far the content is inset from the rect a widget gets: the margin, the inner half of
+    /// the border and the padding.
     pub fn full_border_width(&self) -> f64 {
         (self.padding + self.margin) as f64 + self.border_width as f64 / 2.0
     }
@@ -124,3 +400,36 @@ impl RectTheme {
 pub trait ThemeProvider {
     fn text_theme(&self, variant: WidgetVariant, enabled: bool) -> TextTheme;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(hex_color::parse("#FF7700"), Some(0xFF_77_00_FF));
+        assert_eq!(hex_color::parse("#ff770080"), Some(0xFF_77_00_80));
+        assert_eq!(hex_color::parse("FF7700"), None);
+        assert_eq!(hex_color::parse("#FF770"), None);
+        assert_eq!(hex_color::parse("#+F7700"), None);
+    }
+
+    #[test]
+    fn round_trips() {
+        let theme = Theme::default();
+
+        for loaded in [
+            Theme::from_toml(&theme.to_toml()).unwrap(),
+            Theme::from_ron(&theme.to_ron()).unwrap(),
+        ] {
+            assert_eq!(
+                loaded.text.get(WidgetVariant::Normal, false),
+                theme.text.get(WidgetVariant::Normal, false),
+            );
+            assert_eq!(
+                loaded.rect.enabled.danger.background_color,
+                theme.rect.enabled.danger.background_color,
+            );
+        }
+    }
+}
@@ -1,44 +1,62 @@
 use druid_shell::kurbo::{Point, Rect, Size};
+use druid_shell::{HotKey, KbKey};
 
 // use super::drawables::{checkmark_elem, fixed_rect_elem};
 // use super::or::OrElem;
-use super::{drawables::TextState, *};
+use super::{drawables::TextWidget, *};
 use crate::*;
 
 pub fn text_button<E, F: for<'a> Fn(&'a mut E)>(
     text: &'static str,
     on_click: F,
-) -> impl Widget<E, State = ButtonState<TextState>> {
-    //Button<TextWidget, F> {
+) -> Button<TextWidget, F> {
     Button {
         widget: drawables::text(text),
         on_click,
+        variant: WidgetVariant::Normal,
     }
 }
 
 pub struct ButtonState<S> {
-    pub variant: WidgetVariant,
     pub state: S,
     pub layout: Size,
+    /// How far the content is inset by the background and border of the theme.
+    pub inset: f64,
+    pub interaction: Interaction,
 }
 
 impl<S: WidgetState> WidgetState for ButtonState<S> {
     fn new() -> Self {
         ButtonState {
-            variant: WidgetVariant::Normal,
             state: S::new(),
             layout: Size::ZERO,
+            inset: 0.,
+            interaction: Default::default(),
         }
     }
 
     fn min_size(&self) -> Size {
         self.layout
     }
+
+    fn baseline(&self) -> Option<f64> {
+        self.state.baseline().map(|baseline| baseline + self.inset)
+    }
 }
 
 pub struct Button<E, H> {
     pub widget: E,
     pub on_click: H,
+    pub variant: WidgetVariant,
+}
+
+impl<E, H> Button<E, H> {
+    /// Picks which of the rect themes of the [Theme] the button is drawn with, like
+    /// [WidgetVariant::Danger] for buttons that delete something.
+    pub fn variant(mut self, variant: WidgetVariant) -> Self {
+        self.variant = variant;
+        self
+    }
 }
 
 // impl<'a, E: WidgetParams, H: Fn() + 'a> WidgetParams for ButtonElem<E, H> {
@@ -56,18 +74,14 @@ impl<E, D: Widget<E>, H: Fn(&mut E)> Widget<E> for Button<D, H> {
         constraint: LayoutConstraint,
         ctx: &mut LayoutCtx,
     ) {
-        self.widget.layout(
-            &mut state.state,
-            env,
-            LayoutConstraint {
-                x: constraint.x.map(|w| 0f64.max(w - PADDING)),
-                y: constraint.y.map(|h| 0f64.max(h - PADDING)),
-            },
-            ctx,
-        );
+        state.inset = ctx.theme.rect.get(self.variant, true).full_border_width();
+        let padding = Size::new(state.inset * 2., state.inset * 2.);
+
+        self.widget
+            .layout(&mut state.state, env, constraint.deflate(padding), ctx);
 
         // TODO: These fields are kinda redundant.
-        state.layout = state.state.min_size() + Size::new(PADDING * 2., PADDING * 2.);
+        state.layout = constraint.constrain_min(state.state.min_size() + padding);
         // self.on_click = self.on_click;
     }
 
@@ -89,9 +103,18 @@ impl<E, D: Widget<E>, H: Fn(&mut E)> Widget<E> for Button<D, H> {
         //     layer,
         //     focus,
         // );
+        let theme = ctx.theme.rect.get(self.variant, true);
+        // Another widget can take the focus without this one getting any input, so the ring
+        // follows `focus` rather than what the input last told it.
+        let interaction = Interaction {
+            focused: focus,
+            ..state.interaction
+        };
+
         render_rect(
-            false,
+            &theme,
             true,
+            interaction,
             &mut self.widget,
             &mut state.state,
             env,
@@ -112,20 +135,79 @@ impl<E, D: Widget<E>, H: Fn(&mut E)> Widget<E> for Button<D, H> {
         input: CursorInput,
         _input_state: &InputState,
         _theme: &Theme,
-        _: bool,
+        focus: bool,
+        ctx: &mut InputCtx,
     ) -> InputReturn {
         // let rect_theme = theme.rect.get(self.variant, true);
 
+        let interaction = &mut state.interaction;
+        interaction.focused = focus;
+        interaction.update_hot(rect, cursor_pos, ctx);
+
         match input {
-            CursorInput::Up(..) => {
-                if rect.contains(cursor_pos) {
+            CursorInput::Down(..) if interaction.hot => {
+                interaction.active = true;
+                ctx.invalidate_rect(rect);
+            }
+            // only a press that started on the button clicks it
+            CursorInput::Up(..) if interaction.active => {
+                interaction.active = false;
+
+                if interaction.hot {
                     (self.on_click)(env);
+                    ctx.invalidate();
+                } else {
+                    ctx.invalidate_rect(rect);
                 }
             }
             _ => (),
         }
         Default::default()
     }
+
+    /// A focused button can also be clicked with Enter or Space.
+    fn handle_keyboard_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        _rect: Rect,
+        input: &KeyboardInput,
+        _input_state: &InputState,
+        _theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        state.interaction.focused = focus;
+
+        match input {
+            KeyboardInput::KeyDown(event) => {
+                if focus
+                    && (HotKey::new(None, KbKey::Enter).matches(event)
+                        || HotKey::new(None, " ").matches(event))
+                {
+                    (self.on_click)(env);
+                    ctx.invalidate();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn accepts_focus(&mut self, _state: &mut Self::State, _env: &mut E) -> bool {
+        true
+    }
+
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        _env: &mut E,
+        _direction: FocusDirection,
+        focus: bool,
+        _ctx: &mut InputCtx,
+    ) -> bool {
+        state.interaction.focused = !focus;
+        state.interaction.focused
+    }
 }
 
 // #[derive(Copy, Clone, Debug, Default)]
@@ -302,3 +384,61 @@ impl<E, D: Widget<E>, H: Fn(&mut E)> Widget<E> for Button<D, H> {
 //         }
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flex_content;
+    use crate::testing::TestHarness;
+    use crate::widgets::lists::row;
+    use druid_shell::Modifiers;
+
+    #[test]
+    fn click_button() {
+        let mut harness = TestHarness::with_env(
+            text_button("Click", |clicks: &mut u32| *clicks += 1),
+            0,
+            (200., 100.),
+        );
+
+        harness.click((100., 50.));
+        assert_eq!(*harness.env(), 1);
+
+        harness.click((300., 50.));
+        assert_eq!(*harness.env(), 1);
+    }
+
+    #[test]
+    fn only_presses_that_start_on_a_button_click_it() {
+        let mut harness = TestHarness::with_env(
+            row(flex_content![
+                text_button("A", |clicks: &mut [u32; 2]| clicks[0] += 1),
+                text_button("B", |clicks: &mut [u32; 2]| clicks[1] += 1),
+            ]),
+            [0, 0],
+            (200., 100.),
+        );
+
+        // dragging from one button to the other clicks neither of them
+        harness.mouse_down((50., 50.), MouseButton::Primary);
+        harness.move_to((150., 50.));
+        harness.mouse_up((150., 50.), MouseButton::Primary);
+        assert_eq!(*harness.env(), [0, 0]);
+
+        // the press is kept while the cursor is somewhere else
+        harness.mouse_down((50., 50.), MouseButton::Primary);
+        harness.move_to((150., 50.));
+        harness.move_to((50., 50.));
+        harness.mouse_up((50., 50.), MouseButton::Primary);
+        assert_eq!(*harness.env(), [1, 0]);
+    }
+
+    #[test]
+    fn moving_the_focus_marks_the_button_focused() {
+        let mut harness = TestHarness::new(text_button("A", |_: &mut Runtime| ()), (100., 50.));
+        assert!(!harness.state().interaction.focused);
+
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        assert!(harness.state().interaction.focused);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::*;
-use super::{button::Button, or::OrWidget, drawables::{Checkmark, FixedRect}, NoneWidget};
+use super::{button::Button, or::OrWidget, drawables::{Checkmark, FixedRect}, NoneWidget, WidgetVariant};
 
 pub fn checkbox<E>(checked: bool, on_click: impl Fn(&mut E)) -> impl Widget<E> {
     Button {
@@ -9,5 +9,6 @@ pub fn checkbox<E>(checked: bool, on_click: impl Fn(&mut E)) -> impl Widget<E> {
             OrWidget::B(FixedRect(Size::new(16., 16.)))
         },
         on_click,
+        variant: WidgetVariant::Normal,
     }
 }
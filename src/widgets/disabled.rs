@@ -0,0 +1,289 @@
+use druid_shell::kurbo::{Point, Rect, Size};
+
+use super::*;
+use crate::*;
+
+/// Shows `child` disabled: it gets no cursor or keyboard input, so buttons don't click and
+/// textboxes can't be edited, the focus skips it and it's drawn with the disabled theme.
+pub fn disabled<E, W: Widget<E>>(child: W) -> impl Widget<E, State = DisabledState<W::State>> {
+    enabled(false, child)
+}
+
+/// Like [disabled], but only disables `child` if `enabled` is false, for widgets that are disabled
+/// depending on the environment.
+pub fn enabled<E, W: Widget<E>>(
+    enabled: bool,
+    child: W,
+) -> impl Widget<E, State = DisabledState<W::State>> {
+    Disabled { enabled, child }
+}
+
+pub struct Disabled<W> {
+    enabled: bool,
+    child: W,
+}
+
+pub struct DisabledState<S> {
+    child: S,
+}
+
+impl<S: WidgetState> WidgetState for DisabledState<S> {
+    fn new() -> Self {
+        DisabledState { child: S::new() }
+    }
+
+    fn min_size(&self) -> Size {
+        self.child.min_size()
+    }
+
+    fn extra_layers(&self) -> u8 {
+        self.child.extra_layers()
+    }
+
+    fn baseline(&self) -> Option<f64> {
+        self.child.baseline()
+    }
+}
+
+impl<E, W: Widget<E>> Widget<E> for Disabled<W> {
+    type State = DisabledState<W::State>;
+
+    fn layout(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        constraint: LayoutConstraint,
+        ctx: &mut LayoutCtx,
+    ) {
+        if self.enabled {
+            self.child.layout(&mut state.child, env, constraint, ctx);
+            return;
+        }
+
+        // derived in every pass, since the child might have been enabled in the last one
+        let theme = ctx.theme.to_disabled();
+
+        self.child.layout(
+            &mut state.child,
+            env,
+            constraint,
+            &mut LayoutCtx {
+                text: &mut *ctx.text,
+                theme: &theme,
+                timers: &mut *ctx.timers,
+                relayout: ctx.relayout,
+            },
+        );
+    }
+
+    fn render(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        layer: u8,
+        focus: bool,
+        ctx: &mut RenderCtx,
+    ) {
+        if self.enabled {
+            self.child
+                .render(&mut state.child, env, rect, layer, focus, ctx);
+            return;
+        }
+
+        // the child doesn't react to the cursor, so it isn't drawn hovered either
+        let input_state = InputState {
+            cursor_pos: None,
+            ..ctx.input_state.clone()
+        };
+
+        self.child.render(
+            &mut state.child,
+            env,
+            rect,
+            layer,
+            false,
+            &mut RenderCtx {
+                piet: &mut *ctx.piet,
+                theme: &ctx.theme.to_disabled(),
+                input_state: &input_state,
+            },
+        );
+    }
+
+    fn test_input_pos_layer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input_pos: Point,
+    ) -> Option<u8> {
+        // the input still hits the child, it just doesn't get to handle it
+        self.child
+            .test_input_pos_layer(&mut state.child, env, rect, input_pos)
+    }
+
+    fn handle_cursor_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        cursor_pos: Point,
+        cursor_layer: u8,
+        input: CursorInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> InputReturn {
+        if !self.enabled {
+            // A press that started before the child was disabled still has to end, but without
+            // clicking anything.
+            if let CursorInput::Up(..) = input {
+                self.child.handle_cursor_input(
+                    &mut state.child,
+                    env,
+                    rect,
+                    OUTSIDE,
+                    cursor_layer,
+                    input,
+                    input_state,
+                    theme,
+                    false,
+                    ctx,
+                );
+            }
+
+            return Default::default();
+        }
+
+        self.child.handle_cursor_input(
+            &mut state.child,
+            env,
+            rect,
+            cursor_pos,
+            cursor_layer,
+            input,
+            input_state,
+            theme,
+            focus,
+            ctx,
+        )
+    }
+
+    fn handle_keyboard_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input: &KeyboardInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        if self.enabled {
+            self.child.handle_keyboard_input(
+                &mut state.child,
+                env,
+                rect,
+                input,
+                input_state,
+                theme,
+                focus,
+                ctx,
+            );
+        }
+    }
+
+    fn accepts_focus(&mut self, state: &mut Self::State, env: &mut E) -> bool {
+        self.enabled && self.child.accepts_focus(&mut state.child, env)
+    }
+
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        // passing the focus on right away makes it skip the child
+        self.enabled
+            && self
+                .child
+                .move_focus(&mut state.child, env, direction, focus, ctx)
+    }
+
+    fn handle_timer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        self.child
+            .handle_timer(&mut state.child, env, token, focus && self.enabled, ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flex_content;
+    use crate::testing::TestHarness;
+    use crate::widgets::button::text_button;
+    use crate::widgets::lists::{row, virtual_list};
+    use druid_shell::{KbKey, Modifiers};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn disabled_buttons_ignore_clicks_and_focus() {
+        let mut harness = TestHarness::with_env(
+            row(flex_content![
+                text_button("A", |clicks: &mut [u32; 3]| clicks[0] += 1),
+                disabled(text_button("B", |clicks: &mut [u32; 3]| clicks[1] += 1)),
+                enabled(true, text_button("C", |clicks: &mut [u32; 3]| clicks[2] += 1)),
+            ]),
+            [0, 0, 0],
+            (300., 100.),
+        );
+
+        harness.click((150., 50.));
+        harness.click((250., 50.));
+        assert_eq!(*harness.env(), [0, 0, 1]);
+
+        // the second tab skips the disabled button
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Enter));
+        assert_eq!(*harness.env(), [0, 0, 2]);
+    }
+
+    #[test]
+    fn disabling_a_pressed_button_ends_the_press() {
+        let is_enabled = Rc::new(Cell::new(true));
+        let flag = is_enabled.clone();
+
+        // the rows of a virtual list are built again for every pass, so the flag reaches them
+        let mut harness = TestHarness::with_env(
+            virtual_list(1, 30., move |_| {
+                enabled(flag.get(), text_button("A", |clicks: &mut u32| *clicks += 1))
+            }),
+            0,
+            (100., 100.),
+        );
+
+        harness.mouse_down((5., 5.), MouseButton::Primary);
+        is_enabled.set(false);
+        harness.mouse_up((5., 5.), MouseButton::Primary);
+        assert_eq!(*harness.env(), 0);
+
+        // enabled again, a release without a press doesn't click
+        is_enabled.set(true);
+        harness.mouse_up((5., 5.), MouseButton::Primary);
+        assert_eq!(*harness.env(), 0);
+    }
+}
@@ -3,33 +3,61 @@ use super::*;
 use crate::*;
 
 use druid_shell::kurbo::{BezPath, PathEl};
-use druid_shell::piet::{Color, Text, TextLayout, TextLayoutBuilder};
-use piet_common::Piet;
+use druid_shell::piet::{Color, FontFamily, Text, TextLayout, TextLayoutBuilder};
+use piet_common::{Piet, PietTextLayout};
 use piet_common::RenderContext;
 
-pub fn text<E>(text: &'static str) -> impl Widget<E, State = TextState> {
-    TextWidget(text)
+pub fn text(text: &'static str) -> TextWidget {
+    TextWidget {
+        text,
+        variant: WidgetVariant::Normal,
+    }
 }
 
-pub struct TextWidget(&'static str);
+pub struct TextWidget {
+    text: &'static str,
+    variant: WidgetVariant,
+}
+
+impl TextWidget {
+    /// Picks which of the text themes of the [Theme] the text is drawn with.
+    pub fn variant(mut self, variant: WidgetVariant) -> Self {
+        self.variant = variant;
+        self
+    }
+}
 
 pub struct TextState {
-    pub variant: WidgetVariant,
     // pub text: &'static str,
     pub layout: Size,
+    baseline: f64,
+    text_layout: Option<CachedTextLayout>,
+}
+
+/// A text layout together with what it was built from, so it only has to be built again when one
+/// of those changes.
+struct CachedTextLayout {
+    text: &'static str,
+    theme: TextTheme,
+    layout: PietTextLayout,
 }
 
 impl WidgetState for TextState {
     fn new() -> Self {
         TextState {
-            variant: WidgetVariant::Normal,
             layout: Size::ZERO,
+            baseline: 0.,
+            text_layout: None,
         }
     }
 
     fn min_size(&self) -> Size {
         self.layout
     }
+
+    fn baseline(&self) -> Option<f64> {
+        Some(self.baseline)
+    }
 }
 
 impl<E> Widget<E> for TextWidget {
@@ -42,31 +70,38 @@ impl<E> Widget<E> for TextWidget {
         constraint: LayoutConstraint,
         ctx: &mut LayoutCtx,
     ) {
-        let theme = ctx.theme.text.get(state.variant, true);
-
-        let font = ctx.text.font_family(theme.font).unwrap();
-        let layout = ctx.text
-            .new_text_layout(self.0)
-            .font(font, theme.size as f64)
-            .build()
-            .unwrap();
-
-        let layout_size = layout.size();
-        let min_width: f64 = layout_size.width;
-        let min_height: f64 = layout_size.height;
-
-        state.layout = Size::new(
-            if let Some(max_width) = constraint.x {
-                min_width.max(max_width)
-            } else {
-                min_width
-            },
-            if let Some(max_height) = constraint.y {
-                min_height.max(max_height)
-            } else {
-                min_height
-            },
-        );
+        let theme = ctx.theme.text.get(self.variant, true);
+
+        let cached = match state.text_layout.take() {
+            Some(cached) if cached.text == self.text && cached.theme == theme => cached,
+            _ => {
+                let font = ctx
+                    .text
+                    .font_family(&theme.font)
+                    .unwrap_or(FontFamily::SYSTEM_UI);
+                let layout = ctx.text
+                    .new_text_layout(self.text)
+                    .font(font, theme.size as f64)
+                    .text_color(Color::Rgba32(theme.color))
+                    .build()
+                    .unwrap();
+
+                CachedTextLayout {
+                    text: self.text,
+                    theme,
+                    layout,
+                }
+            }
+        };
+
+        // the text doesn't wrap, so it can't get any narrower than this
+        state.layout = constraint.constrain_min(cached.layout.size());
+
+        // the text is centered vertically when rendered
+        let line_metric = cached.layout.line_metric(0).unwrap();
+        state.baseline = (state.layout.height - line_metric.height) / 2. + line_metric.baseline;
+
+        state.text_layout = Some(cached);
     }
 
     // fn measure(
@@ -115,23 +150,18 @@ impl<E> Widget<E> for TextWidget {
         _: bool,
         ctx: &mut RenderCtx,
     ) {
-        let theme = ctx.theme.text.get(state.variant, true);
-
-        let text_factory = ctx.piet.text();
-        let font = text_factory.font_family(theme.font).unwrap();
-        let layout = text_factory
-            .new_text_layout(self.0)
-            .font(font, theme.size as f64)
-            .text_color(Color::WHITE)
-            .build()
-            .unwrap();
+        // the layout pass always runs first and built this
+        let layout = match &state.text_layout {
+            Some(cached) => &cached.layout,
+            None => return,
+        };
 
         let text_width = layout.size().width;
 
         let line_metric = layout.line_metric(0).unwrap();
 
         ctx.piet.draw_text(
-            &layout,
+            layout,
             (
                 rect.x0 + (rect.width() - text_width) / 2.0,
                 (rect.y0 + rect.height() / 2.0) - line_metric.height / 2.0,
@@ -174,10 +204,10 @@ impl<E> Widget<E> for Checkmark {
         &mut self,
         state: &mut Self::State,
         _env: &mut E,
-        _constraint: LayoutConstraint,
+        constraint: LayoutConstraint,
         _ctx: &mut LayoutCtx,
     ) {
-        state.0 = self.0
+        state.0 = constraint.constrain_min(self.0)
     }
 
     // fn measure(
@@ -209,7 +239,13 @@ impl<E> Widget<E> for Checkmark {
                 PathEl::LineTo((rect.x0 + rect.width() / 3., rect.y1 - rect.height() / 6.).into()),
                 PathEl::LineTo((rect.x1, rect.y0 + rect.height() / 6.).into()),
             ]),
-            &Color::Rgba32(0xFF_FF_FF_FF),
+            &Color::Rgba32(
+                ctx.theme
+                    .rect
+                    .get(WidgetVariant::Normal, true)
+                    .foreground_color
+                    .0,
+            ),
             4.0,
         );
     }
@@ -250,7 +286,7 @@ impl<E> Widget<E> for FixedRect {
         constraint: LayoutConstraint,
         ctx: &mut LayoutCtx,
     ) {
-        state.0 = self.0;
+        state.0 = constraint.constrain_min(self.0);
     }
 }
 
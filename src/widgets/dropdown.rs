@@ -60,6 +60,10 @@ impl WidgetState for State {
     fn extra_layers(&self) -> u8 {
         self.widget_state.extra_layers()
     }
+
+    fn baseline(&self) -> Option<f64> {
+        self.widget_state.baseline()
+    }
 }
 
 pub struct Dropdown<J, H> {
@@ -87,7 +91,7 @@ impl<E, I, J: Iterator<Item = (I, &'static str)> + Clone, H: Fn(&mut E, &I)> Pas
                             *e.0 = false;
                             on_select(e.1, &ident);
                         }),
-                        expand: true,
+                        flex: 1.,
                     }),
                 }))
             } else {
@@ -21,6 +21,7 @@ pub struct StatefulWidgetState<S> {
     widget_state: Box<dyn Any>,
     min_size: Size,
     extra_layers: u8,
+    baseline: Option<f64>,
 }
 
 impl<S: Default> WidgetState for StatefulWidgetState<S> {
@@ -30,6 +31,7 @@ impl<S: Default> WidgetState for StatefulWidgetState<S> {
             widget_state: Box::new(()),
             min_size: Size::ZERO,
             extra_layers: 0,
+            baseline: None,
         }
     }
 
@@ -40,6 +42,10 @@ impl<S: Default> WidgetState for StatefulWidgetState<S> {
     fn extra_layers(&self) -> u8 {
         self.extra_layers
     }
+
+    fn baseline(&self) -> Option<f64> {
+        self.baseline
+    }
 }
 
 impl<E, S: Default, B: Fn(StatefulWidgetHandler<E, S>)> Widget<E> for StatefulWidget<S, B> {
@@ -112,6 +118,7 @@ impl<E, S: Default, B: Fn(StatefulWidgetHandler<E, S>)> Widget<E> for StatefulWi
         input_state: &InputState,
         theme: &Theme,
         focus: bool,
+        ctx: &mut InputCtx,
     ) -> InputReturn {
         let mut result = None;
         (self.build)(StatefulWidgetHandler {
@@ -125,6 +132,7 @@ impl<E, S: Default, B: Fn(StatefulWidgetHandler<E, S>)> Widget<E> for StatefulWi
                 input_state,
                 theme,
                 focus,
+                ctx,
                 result: &mut result,
             },
         });
@@ -141,6 +149,7 @@ impl<E, S: Default, B: Fn(StatefulWidgetHandler<E, S>)> Widget<E> for StatefulWi
         input_state: &InputState,
         theme: &Theme,
         focus: bool,
+        ctx: &mut InputCtx,
     ) {
         (self.build)(StatefulWidgetHandler {
             env,
@@ -151,9 +160,61 @@ impl<E, S: Default, B: Fn(StatefulWidgetHandler<E, S>)> Widget<E> for StatefulWi
                 input_state,
                 theme,
                 focus,
+                ctx,
             },
         });
     }
+
+    fn accepts_focus(&mut self, state: &mut Self::State, env: &mut E) -> bool {
+        let mut result = false;
+        (self.build)(StatefulWidgetHandler {
+            env,
+            state,
+            handler: BuiltStatefulWidgetHandler::AcceptsFocus {
+                result: &mut result,
+            },
+        });
+
+        result
+    }
+
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        let mut result = false;
+        (self.build)(StatefulWidgetHandler {
+            env,
+            state,
+            handler: BuiltStatefulWidgetHandler::MoveFocus {
+                direction,
+                focus,
+                ctx,
+                result: &mut result,
+            },
+        });
+
+        result
+    }
+
+    fn handle_timer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        (self.build)(StatefulWidgetHandler {
+            env,
+            state,
+            handler: BuiltStatefulWidgetHandler::HandleTimer { token, focus, ctx },
+        });
+    }
 }
 
 pub struct StatefulWidgetHandler<'e, 's, 'c, 'ca, 'cb, 'r, 't, 'is, E, S> {
@@ -192,6 +253,7 @@ enum BuiltStatefulWidgetHandler<'c, 'ca, 'cb, 'r, 't, 'is> {
         input_state: &'ca InputState,
         theme: &'ca Theme,
         focus: bool,
+        ctx: &'c mut InputCtx<'cb>,
         result: &'r mut Option<InputReturn>,
     },
     HandleKeyboardInput {
@@ -200,6 +262,21 @@ enum BuiltStatefulWidgetHandler<'c, 'ca, 'cb, 'r, 't, 'is> {
         input_state: &'ca InputState,
         theme: &'ca Theme,
         focus: bool,
+        ctx: &'c mut InputCtx<'cb>,
+    },
+    AcceptsFocus {
+        result: &'r mut bool,
+    },
+    MoveFocus {
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &'c mut InputCtx<'cb>,
+        result: &'r mut bool,
+    },
+    HandleTimer {
+        token: TimerToken,
+        focus: bool,
+        ctx: &'c mut InputCtx<'cb>,
     },
 }
 
@@ -236,6 +313,7 @@ impl<'e, 's, 'c, 'ca, 'cb, 'r, 't, 'is, E, S>
 
                 state.min_size = widget_state.min_size();
                 state.extra_layers = widget_state.extra_layers();
+                state.baseline = widget_state.baseline();
             }
             Render {
                 rect,
@@ -276,6 +354,7 @@ impl<'e, 's, 'c, 'ca, 'cb, 'r, 't, 'is, E, S>
                 input_state,
                 theme,
                 focus,
+                ctx,
                 result,
             } => {
                 let widget_state = state.widget_state.downcast_mut::<WS>().unwrap();
@@ -290,6 +369,7 @@ impl<'e, 's, 'c, 'ca, 'cb, 'r, 't, 'is, E, S>
                     input_state,
                     theme,
                     focus,
+                    ctx,
                 ));
             }
             HandleKeyboardInput {
@@ -298,6 +378,7 @@ impl<'e, 's, 'c, 'ca, 'cb, 'r, 't, 'is, E, S>
                 input_state,
                 theme,
                 focus,
+                ctx,
             } => {
                 let widget_state = state.widget_state.downcast_mut::<WS>().unwrap();
 
@@ -309,8 +390,42 @@ impl<'e, 's, 'c, 'ca, 'cb, 'r, 't, 'is, E, S>
                     input_state,
                     theme,
                     focus,
+                    ctx,
                 );
             }
+            AcceptsFocus { result } => {
+                let widget_state = state.widget_state.downcast_mut::<WS>().unwrap();
+
+                *result = widget.accepts_focus(widget_state, &mut (&mut state.state, env));
+            }
+            MoveFocus {
+                direction,
+                focus,
+                ctx,
+                result,
+            } => {
+                let widget_state = state.widget_state.downcast_mut::<WS>().unwrap();
+
+                *result = widget.move_focus(
+                    widget_state,
+                    &mut (&mut state.state, env),
+                    direction,
+                    focus,
+                    ctx,
+                );
+            }
+            HandleTimer { token, focus, ctx } => {
+                // the widget might not have been laid out since it was built
+                if let Some(widget_state) = state.widget_state.downcast_mut::<WS>() {
+                    widget.handle_timer(
+                        widget_state,
+                        &mut (&mut state.state, env),
+                        token,
+                        focus,
+                        ctx,
+                    );
+                }
+            }
         }
     }
 }
@@ -2,6 +2,152 @@ use piet_common::Piet;
 
 use super::*;
 
+/// How the children of a [Row] or [Col] are placed along it when none of them expand and there is
+/// space left over.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MainAxisAlignment {
+    /// All together at the left of a row or the top of a column.
+    Start,
+    Center,
+    /// All together at the right of a row or the bottom of a column.
+    End,
+    /// The first and last child at the ends, with the space left over between the children.
+    SpaceBetween,
+    /// Half as much space before the first and after the last child as between the others.
+    SpaceAround,
+}
+
+/// Places children along the main axis of a flex container.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(super) struct MainPlacement {
+    /// The space before the first child.
+    pub(super) lead: f64,
+    /// The space after every child but the last.
+    pub(super) gap: f64,
+    /// The length an expanding child gets per unit of its flex factor.
+    pub(super) flex_unit: f64,
+}
+
+impl MainPlacement {
+    /// `fixed_length` is what the children that don't expand take up together and `count` is the
+    /// number of all children.
+    pub(super) fn new(
+        alignment: MainAxisAlignment,
+        available: f64,
+        fixed_length: f64,
+        flex_total: f64,
+        spacing: f64,
+        count: u32,
+    ) -> Self {
+        let spacing_total = spacing * count.saturating_sub(1) as f64;
+        // when the children don't fit they just overflow at the end
+        let free = 0f64.max(available - fixed_length - spacing_total);
+
+        if flex_total > 0. {
+            return MainPlacement {
+                lead: 0.,
+                gap: spacing,
+                flex_unit: free / flex_total,
+            };
+        }
+
+        let (lead, gap) = match alignment {
+            MainAxisAlignment::Start => (0., spacing),
+            MainAxisAlignment::Center => (free / 2., spacing),
+            MainAxisAlignment::End => (free, spacing),
+            MainAxisAlignment::SpaceBetween if count > 1 => {
+                (0., spacing + free / (count - 1) as f64)
+            }
+            MainAxisAlignment::SpaceBetween => (0., spacing),
+            MainAxisAlignment::SpaceAround if count > 0 => {
+                (free / count as f64 / 2., spacing + free / count as f64)
+            }
+            MainAxisAlignment::SpaceAround => (0., spacing),
+        };
+
+        MainPlacement {
+            lead,
+            gap,
+            flex_unit: 0.,
+        }
+    }
+
+    fn length(&self, flex: f64, min_length: f64) -> f64 {
+        if flex > 0. {
+            flex * self.flex_unit
+        } else {
+            min_length
+        }
+    }
+}
+
+/// How the children of a [Row] or [Col] are placed on the axis across it, when they get more
+/// space there than they asked for.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CrossAxisAlignment {
+    /// At the top of a row or the left of a column.
+    Start,
+    Center,
+    /// At the bottom of a row or the right of a column.
+    End,
+    /// Across all of the space.
+    Stretch,
+    /// With the first baselines of all children on one line, so text in a row lines up. Children
+    /// without a baseline are placed at the start, and so are all children of a column.
+    Baseline,
+}
+
+/// Places children on the cross axis of a flex container.
+#[derive(Copy, Clone, Debug)]
+pub(super) struct CrossPlacement {
+    pub(super) alignment: CrossAxisAlignment,
+    /// Where the baseline of the children that have one is, from the start of the cross axis.
+    pub(super) baseline: Option<f64>,
+}
+
+/// How far the children of a row reach above and below their baselines.
+#[derive(Copy, Clone, Default, Debug)]
+pub(super) struct BaselineExtent {
+    pub(super) ascent: Option<f64>,
+    descent: f64,
+}
+
+impl BaselineExtent {
+    pub(super) fn add(&mut self, height: f64, baseline: Option<f64>) {
+        if let Some(baseline) = baseline {
+            self.ascent = Some(self.ascent.map_or(baseline, |ascent| ascent.max(baseline)));
+            self.descent = self.descent.max(height - baseline);
+        }
+    }
+
+    /// The height needed to line up all the children on one baseline.
+    pub(super) fn height(&self) -> f64 {
+        self.ascent.map_or(0., |ascent| ascent + self.descent)
+    }
+}
+
+impl CrossPlacement {
+    /// The offset and the length of a child on the cross axis when the container has `available`
+    /// there.
+    pub(super) fn place(
+        &self,
+        available: f64,
+        child_length: f64,
+        child_baseline: Option<f64>,
+    ) -> (f64, f64) {
+        match self.alignment {
+            CrossAxisAlignment::Start => (0., child_length),
+            CrossAxisAlignment::Center => ((available - child_length) / 2., child_length),
+            CrossAxisAlignment::End => (available - child_length, child_length),
+            CrossAxisAlignment::Stretch => (0., available),
+            CrossAxisAlignment::Baseline => match (self.baseline, child_baseline) {
+                (Some(baseline), Some(child_baseline)) => (baseline - child_baseline, child_length),
+                _ => (0., child_length),
+            },
+        }
+    }
+}
+
 macro_rules! flex {
     (
         $struct:ident,
@@ -10,40 +156,81 @@ macro_rules! flex {
         $primary_axis:ident,
         $secondary_axis:ident,
         $primary_size:ident,
-        $secondary_size:ident
+        $secondary_size:ident,
+        $baselines:literal
     ) => {
-        pub fn $function<E, C: FlexContent<E>>(
-            content: C,
-        ) -> impl Widget<E, State = $state_struct<C::State>> {
-            $struct { content }
+        pub fn $function<C>(content: C) -> $struct<C> {
+            $struct {
+                content,
+                spacing: 0.,
+                main_axis_alignment: MainAxisAlignment::Start,
+                cross_axis_alignment: CrossAxisAlignment::Stretch,
+            }
         }
 
         pub struct $struct<C> {
             content: C,
+            spacing: f64,
+            main_axis_alignment: MainAxisAlignment,
+            cross_axis_alignment: CrossAxisAlignment,
+        }
+
+        impl<C> $struct<C> {
+            /// The space between two neighbouring children.
+            pub fn spacing(mut self, spacing: f64) -> Self {
+                self.spacing = spacing;
+                self
+            }
+
+            /// Where to put the children when there is space left over. This only matters when
+            /// none of them expand, since otherwise they take up all of it.
+            pub fn main_axis_alignment(mut self, alignment: MainAxisAlignment) -> Self {
+                self.main_axis_alignment = alignment;
+                self
+            }
+
+            /// How to place children that get more space across the container than they asked
+            /// for. By default they are stretched.
+            pub fn cross_axis_alignment(mut self, alignment: CrossAxisAlignment) -> Self {
+                self.cross_axis_alignment = alignment;
+                self
+            }
         }
 
         pub struct $state_struct<S> {
             content_state: S,
 
-            // TODO: find a way to make this not dependent on order; like the keys common in react-style
-            // frameworks
-            focus: Option<u16>,
-
             size: Size,
-            no_expand_size: f64,
-            expand_count: u32,
+            fixed_length: f64,
+            flex_total: f64,
+            child_count: u32,
             extra_layers: u8,
+            baseline: Option<f64>,
+        }
+
+        impl<S> $state_struct<S> {
+            fn main_placement<C>(&self, widget: &$struct<C>, rect: Rect) -> MainPlacement {
+                MainPlacement::new(
+                    widget.main_axis_alignment,
+                    rect.$primary_size(),
+                    self.fixed_length,
+                    self.flex_total,
+                    widget.spacing,
+                    self.child_count,
+                )
+            }
         }
 
         impl<S: FlexContentState> WidgetState for $state_struct<S> {
             fn new() -> Self {
                 Self {
                     content_state: S::new(),
-                    focus: None,
                     size: Size::ZERO,
-                    no_expand_size: 0.,
-                    expand_count: 0,
+                    fixed_length: 0.,
+                    flex_total: 0.,
+                    child_count: 0,
                     extra_layers: 0,
+                    baseline: None,
                 }
             }
 
@@ -54,6 +241,11 @@ macro_rules! flex {
             fn extra_layers(&self) -> u8 {
                 self.extra_layers
             }
+
+            /// Only known when the children are aligned on their baselines.
+            fn baseline(&self) -> Option<f64> {
+                self.baseline
+            }
         }
 
         impl<E, C: FlexContent<E>> Widget<E> for $struct<C> {
@@ -71,8 +263,10 @@ macro_rules! flex {
                     constraint: &'a LayoutConstraint,
                     ctx: &'a mut LayoutCtx<'b, 't>,
                     size: Size,
-                    expand_count: u32,
+                    flex_total: f64,
+                    child_count: u32,
                     extra_layers: u8,
+                    baselines: Option<BaselineExtent>,
                 }
 
                 impl<'a, 'b, 't, E> FlexContentHandler<E> for MeasureHandler<'a, 'b, 't, E> {
@@ -80,17 +274,28 @@ macro_rules! flex {
                         &mut self,
                         widget: &mut W,
                         state: &mut W::State,
-                        expand: bool,
+                        flex: f64,
+                        _: &mut bool,
+                        layout: &mut LayoutCache,
                     ) {
-                        if expand {
-                            self.expand_count += 1;
+                        self.child_count += 1;
+
+                        if flex > 0. {
+                            self.flex_total += flex;
                         } else {
-                            widget.layout(
+                            layout.layout(
+                                widget,
                                 state,
                                 self.env,
                                 LayoutConstraint {
-                                    $primary_axis: None,
-                                    $secondary_axis: self.constraint.$secondary_axis,
+                                    min: Size {
+                                        $primary_size: 0.,
+                                        $secondary_size: self.constraint.min.$secondary_size,
+                                    },
+                                    max: Size {
+                                        $primary_size: f64::INFINITY,
+                                        $secondary_size: self.constraint.max.$secondary_size,
+                                    },
                                 },
                                 self.ctx,
                             );
@@ -100,35 +305,69 @@ macro_rules! flex {
                             self.size.$secondary_size =
                                 self.size.$secondary_size.max(min_size.$secondary_size);
 
+                            if let Some(baselines) = &mut self.baselines {
+                                baselines.add(min_size.height, state.baseline());
+                            }
+
                             self.extra_layers = self.extra_layers.max(state.extra_layers());
                         }
                     }
                 }
 
+                // Only stretched children are made to fill the cross axis right away. The others
+                // tell us how much they need there, so they can be aligned within the rest.
+                let stretch = self.cross_axis_alignment == CrossAxisAlignment::Stretch;
+                let child_constraint = if stretch {
+                    constraint
+                } else {
+                    LayoutConstraint {
+                        min: Size {
+                            $primary_size: constraint.min.$primary_size,
+                            $secondary_size: 0.,
+                        },
+                        max: constraint.max,
+                    }
+                };
+
+                let align_baselines =
+                    $baselines && self.cross_axis_alignment == CrossAxisAlignment::Baseline;
+
                 let mut handler = MeasureHandler {
                     env,
-                    constraint: &constraint,
+                    constraint: &child_constraint,
                     ctx,
                     size: Size::ZERO,
-                    expand_count: 0,
+                    flex_total: 0.,
+                    child_count: 0,
                     extra_layers: 0,
+                    baselines: if align_baselines {
+                        Some(BaselineExtent::default())
+                    } else {
+                        None
+                    },
                 };
 
                 self.content.all(&mut state.content_state, &mut handler);
 
-                state.expand_count = handler.expand_count;
+                state.flex_total = handler.flex_total;
+                state.child_count = handler.child_count;
                 state.extra_layers = handler.extra_layers;
                 let first_pass_size = handler.size;
+                let baselines = handler.baselines;
 
-                let min_length = first_pass_size.$primary_size;
-                state.no_expand_size = min_length;
+                let fixed_length = first_pass_size.$primary_size;
+                state.fixed_length = fixed_length;
+                let spacing_length = self.spacing * state.child_count.saturating_sub(1) as f64;
 
                 struct MeasureExpandHandler<'a, 'b, 't, E> {
                     env: &'a mut E,
                     constraint: &'a LayoutConstraint,
+                    /// The length per unit of flex when the length is limited.
+                    flex_unit: Option<f64>,
                     ctx: &'a mut LayoutCtx<'b, 't>,
                     size: Size,
                     extra_layers: u8,
+                    baselines: Option<BaselineExtent>,
                 }
 
                 impl<'a, 'b, 't, E> FlexContentHandler<E> for MeasureExpandHandler<'a, 'b, 't, E> {
@@ -136,46 +375,76 @@ macro_rules! flex {
                         &mut self,
                         widget: &mut W,
                         state: &mut W::State,
-                        expand: bool,
+                        flex: f64,
+                        _: &mut bool,
+                        layout: &mut LayoutCache,
                     ) {
-                        if expand {
-                            widget.layout(state, self.env, *self.constraint, self.ctx);
+                        if flex > 0. {
+                            let mut constraint = *self.constraint;
+                            if let Some(flex_unit) = self.flex_unit {
+                                constraint.min.$primary_size = flex * flex_unit;
+                                constraint.max.$primary_size = flex * flex_unit;
+                            }
+
+                            layout.layout(widget, state, self.env, constraint, self.ctx);
 
                             let min_size = state.min_size();
 
                             self.size.$primary_size += min_size.$primary_size;
                             self.size.$secondary_size =
                                 self.size.$secondary_size.max(min_size.$secondary_size);
+
+                            if let Some(baselines) = &mut self.baselines {
+                                baselines.add(min_size.height, state.baseline());
+                            }
+
                             self.extra_layers = self.extra_layers.max(state.extra_layers());
                         }
                     }
                 }
 
+                // The expanding children share what's left by their flex factors. Without a limit
+                // they just get to be as long as they want.
+                let flex_unit = if constraint.max.$primary_size.is_finite() {
+                    let free = constraint.max.$primary_size - fixed_length - spacing_length;
+                    Some(0f64.max(free) / state.flex_total)
+                } else {
+                    None
+                };
+
                 let mut handler = MeasureExpandHandler {
                     env,
                     constraint: &LayoutConstraint {
-                        $primary_axis: constraint
-                            .$primary_axis
-                            .map(|w| (w - min_length) / state.expand_count as f64),
-                        $secondary_axis: constraint.$secondary_axis,
+                        min: Size {
+                            $primary_size: 0.,
+                            $secondary_size: child_constraint.min.$secondary_size,
+                        },
+                        max: Size {
+                            $primary_size: f64::INFINITY,
+                            $secondary_size: child_constraint.max.$secondary_size,
+                        },
                     },
+                    flex_unit,
                     ctx,
                     size: Size::ZERO,
                     extra_layers: state.extra_layers,
+                    baselines,
                 };
 
                 self.content.all(&mut state.content_state, &mut handler);
 
-                state.size = Size {
-                    $primary_size: constraint
-                        .$primary_axis
-                        .map(|w| w.max(handler.size.$primary_size))
-                        .unwrap_or(handler.size.$primary_size),
-                    $secondary_size: constraint
-                        .$primary_axis
-                        .map(|w| w.max(handler.size.$secondary_size))
-                        .unwrap_or(handler.size.$secondary_size),
-                };
+                let baselines = handler.baselines.unwrap_or_default();
+                state.baseline = baselines.ascent;
+
+                // The children can't be squeezed any further, so this might go beyond the maximum.
+                // Whatever doesn't fit in the end is cut off when rendering.
+                state.size = constraint.constrain_min(Size {
+                    $primary_size: fixed_length + spacing_length + handler.size.$primary_size,
+                    $secondary_size: first_pass_size
+                        .$secondary_size
+                        .max(handler.size.$secondary_size)
+                        .max(baselines.height()),
+                });
 
                 state.extra_layers = handler.extra_layers;
             }
@@ -189,21 +458,16 @@ macro_rules! flex {
                 focus: bool,
                 ctx: &mut RenderCtx,
             ) {
-                let min_length = state.no_expand_size;
-
-                let extra_length = rect.$primary_size() - min_length;
-                let expand_length = extra_length / state.expand_count as f64;
-
                 struct RenderHandler<'a, 'b, 'c, 't, 'is, E> {
                     env: &'a mut E,
-                    expand_length: f64,
+                    main: MainPlacement,
                     layer: u8,
                     extra_layers: u8,
                     pos: Point,
                     size: Size,
-                    focus: Option<u16>,
+                    cross: CrossPlacement,
+                    focus: bool,
                     ctx: &'a mut RenderCtx<'b, 'c, 't, 'is>,
-                    i: u16,
                 }
 
                 impl<'a, 'b, 'c, 't, 'is, E> FlexContentHandler<E>
@@ -213,13 +477,11 @@ macro_rules! flex {
                         &mut self,
                         widget: &mut W,
                         state: &mut W::State,
-                        expand: bool,
+                        flex: f64,
+                        focus: &mut bool,
+                        _: &mut LayoutCache,
                     ) {
-                        let widget_length = if expand {
-                            self.expand_length
-                        } else {
-                            state.min_size().$primary_size
-                        };
+                        let widget_length = self.main.length(flex, state.min_size().$primary_size);
 
                         if self.layer <= state.extra_layers() {
                             let empty_input_state: InputState = Default::default();
@@ -230,18 +492,28 @@ macro_rules! flex {
                                 &empty_input_state
                             };
 
+                            let (offset, length) = self.cross.place(
+                                self.size.$secondary_size,
+                                state.min_size().$secondary_size,
+                                state.baseline(),
+                            );
+                            let mut origin = self.pos;
+                            origin.$secondary_axis += offset;
+
+                            let rect = Rect::from_origin_size(
+                                origin,
+                                Size {
+                                    $primary_size: widget_length,
+                                    $secondary_size: length,
+                                },
+                            );
+
                             widget.render(
                                 state,
                                 self.env,
-                                Rect::from_origin_size(
-                                    self.pos,
-                                    Size {
-                                        $primary_size: widget_length,
-                                        $secondary_size: self.size.$secondary_size,
-                                    },
-                                ),
+                                rect,
                                 self.layer,
-                                self.focus == Some(self.i),
+                                self.focus && *focus,
                                 &mut RenderCtx {
                                     piet: &mut *self.ctx.piet,
                                     input_state: &input_state,
@@ -250,24 +522,42 @@ macro_rules! flex {
                             );
                         }
 
-                        self.pos.$primary_axis += widget_length;
-                        self.i += 1;
+                        self.pos.$primary_axis += widget_length + self.main.gap;
                     }
                 }
 
+                let main = state.main_placement(self, rect);
+                let mut pos = rect.origin();
+                pos.$primary_axis += main.lead;
+
+                // Children that don't fit are cut off instead of drawing over the neighbours. The
+                // layers above are left alone so popups can still go beyond.
+                let overflow = layer == 0 && rect.$primary_size() < state.size.$primary_size;
+                if overflow {
+                    ctx.piet.save().unwrap();
+                    ctx.piet.clip(rect);
+                }
+
                 let mut handler = RenderHandler {
                     env,
-                    expand_length,
+                    main,
                     layer,
                     extra_layers: state.extra_layers,
-                    pos: rect.origin(),
+                    pos,
                     size: rect.size(),
-                    focus: if focus { state.focus } else { None },
-                    ctx,
-                    i: 0,
+                    cross: CrossPlacement {
+                        alignment: self.cross_axis_alignment,
+                        baseline: state.baseline,
+                    },
+                    focus,
+                    ctx: &mut *ctx,
                 };
 
                 self.content.all(&mut state.content_state, &mut handler);
+
+                if overflow {
+                    ctx.piet.restore().unwrap();
+                }
             }
 
             fn handle_cursor_input(
@@ -281,93 +571,120 @@ macro_rules! flex {
                 input_state: &InputState,
                 theme: &Theme,
                 focus: bool,
+                ctx: &mut InputCtx,
             ) -> InputReturn {
-                let min_length = state.no_expand_size;
-
-                let extra_width = rect.$primary_size() - min_length;
-                let expand_length = extra_width / state.expand_count as f64;
-
-                struct CursorInputHandler<'a, E> {
+                struct CursorInputHandler<'a, 'b, E> {
                     env: &'a mut E,
                     pos: Point,
                     size: Size,
-                    expand_length: f64,
+                    main: MainPlacement,
+                    cross: CrossPlacement,
                     cursor_pos: Point,
                     cursor_layer: u8,
                     theme: &'a Theme,
                     input: CursorInput,
                     input_state: &'a InputState,
                     focus: bool,
-                    focus_state: &'a mut Option<u16>,
                     i: u16,
-                    demand_focus: bool,
+                    demand_focus: Option<u16>,
+                    ctx: &'a mut InputCtx<'b>,
                 }
 
-                impl<'a, E> FlexContentHandler<E> for CursorInputHandler<'a, E> {
+                impl<'a, 'b, E> FlexContentHandler<E> for CursorInputHandler<'a, 'b, E> {
                     fn widget<W: Widget<E>>(
                         &mut self,
                         widget: &mut W,
                         state: &mut W::State,
-                        expand: bool,
+                        flex: f64,
+                        focus: &mut bool,
+                        layout: &mut LayoutCache,
                     ) {
-                        let widget_length = if expand {
-                            self.expand_length
-                        } else {
-                            state.min_size().$primary_size
-                        };
+                        let widget_length = self.main.length(flex, state.min_size().$primary_size);
 
                         let extra_layers = state.extra_layers();
 
                         if extra_layers >= self.cursor_layer {
-                            let ret = widget.handle_cursor_input(
-                                state,
-                                self.env,
-                                Rect::from_origin_size(
-                                    self.pos,
-                                    Size {
-                                        $primary_size: widget_length,
-                                        $secondary_size: self.size.$secondary_size,
-                                    },
-                                ),
-                                self.cursor_pos,
-                                self.cursor_layer,
-                                self.input,
-                                self.input_state,
-                                self.theme,
-                                self.focus && *self.focus_state == Some(self.i),
+                            let (offset, length) = self.cross.place(
+                                self.size.$secondary_size,
+                                state.min_size().$secondary_size,
+                                state.baseline(),
                             );
+                            let mut origin = self.pos;
+                            origin.$secondary_axis += offset;
+
+                            let rect = Rect::from_origin_size(
+                                origin,
+                                Size {
+                                    $primary_size: widget_length,
+                                    $secondary_size: length,
+                                },
+                            );
+                            let child_focus = self.focus && *focus;
+
+                            let ret = layout.track(self.ctx, |ctx| {
+                                widget.handle_cursor_input(
+                                    state,
+                                    self.env,
+                                    rect,
+                                    self.cursor_pos,
+                                    self.cursor_layer,
+                                    self.input,
+                                    self.input_state,
+                                    self.theme,
+                                    child_focus,
+                                    ctx,
+                                )
+                            });
 
                             if ret.demand_focus {
-                                *self.focus_state = Some(self.i);
-                                self.demand_focus = true;
+                                *focus = true;
+                                self.demand_focus = Some(self.i);
                             }
                         }
 
-                        self.pos.$primary_axis += widget_length;
+                        self.pos.$primary_axis += widget_length + self.main.gap;
                         self.i += 1;
                     }
                 }
 
+                let main = state.main_placement(self, rect);
+                let mut pos = rect.origin();
+                pos.$primary_axis += main.lead;
+
                 let mut handler = CursorInputHandler {
                     env,
-                    pos: rect.origin(),
+                    pos,
                     size: rect.size(),
-                    expand_length,
+                    main,
+                    cross: CrossPlacement {
+                        alignment: self.cross_axis_alignment,
+                        baseline: state.baseline,
+                    },
                     cursor_pos,
                     cursor_layer,
                     theme,
                     input,
                     input_state,
                     focus,
-                    focus_state: &mut state.focus,
                     i: 0,
-                    demand_focus: false,
+                    demand_focus: None,
+                    ctx,
                 };
 
                 self.content.all(&mut state.content_state, &mut handler);
 
+                let demand_focus = handler.demand_focus;
+
+                if demand_focus.is_some() {
+                    set_content_focus::<E, _>(
+                        &mut self.content,
+                        &mut state.content_state,
+                        demand_focus,
+                    );
+                }
+
                 InputReturn {
-                    demand_focus: handler.demand_focus,
+                    demand_focus: demand_focus.is_some(),
                 }
             }
 
@@ -380,78 +697,175 @@ macro_rules! flex {
                 input_state: &InputState,
                 theme: &Theme,
                 focus: bool,
+                ctx: &mut InputCtx,
             ) {
-                struct KeyboardInputHandler<'a, E> {
+                struct KeyboardInputHandler<'a, 'b, E> {
                     env: &'a mut E,
                     pos: Point,
                     size: Size,
-                    expand_length: f64,
+                    main: MainPlacement,
+                    cross: CrossPlacement,
                     input: &'a KeyboardInput,
                     input_state: &'a InputState,
                     theme: &'a Theme,
-                    focus: Option<u16>,
-                    i: u16,
+                    focus: bool,
+                    ctx: &'a mut InputCtx<'b>,
                 }
 
-                impl<'a, E> FlexContentHandler<E> for KeyboardInputHandler<'a, E> {
+                impl<'a, 'b, E> FlexContentHandler<E> for KeyboardInputHandler<'a, 'b, E> {
                     fn widget<W: Widget<E>>(
                         &mut self,
                         widget: &mut W,
                         state: &mut W::State,
-                        expand: bool,
+                        flex: f64,
+                        focus: &mut bool,
+                        layout: &mut LayoutCache,
                     ) {
-                        let widget_length = if expand {
-                            self.expand_length
-                        } else {
-                            state.min_size().$primary_size
-                        };
+                        let widget_length = self.main.length(flex, state.min_size().$primary_size);
 
-                        let focus = self.focus == Some(self.i);
+                        let focus = self.focus && *focus;
                         if focus {
-                            widget.handle_keyboard_input(
-                                state,
-                                self.env,
-                                Rect::from_origin_size(
-                                    self.pos,
-                                    Size {
-                                        $primary_size: widget_length,
-                                        $secondary_size: self.size.$secondary_size,
-                                    },
-                                ),
-                                self.input,
-                                self.input_state,
-                                self.theme,
-                                focus,
+                            let (offset, length) = self.cross.place(
+                                self.size.$secondary_size,
+                                state.min_size().$secondary_size,
+                                state.baseline(),
                             );
+                            let mut origin = self.pos;
+                            origin.$secondary_axis += offset;
+
+                            let rect = Rect::from_origin_size(
+                                origin,
+                                Size {
+                                    $primary_size: widget_length,
+                                    $secondary_size: length,
+                                },
+                            );
+
+                            layout.track(self.ctx, |ctx| {
+                                widget.handle_keyboard_input(
+                                    state,
+                                    self.env,
+                                    rect,
+                                    self.input,
+                                    self.input_state,
+                                    self.theme,
+                                    focus,
+                                    ctx,
+                                )
+                            });
                         }
 
-                        self.pos.$primary_axis += widget_length;
-                        self.i += 1;
+                        self.pos.$primary_axis += widget_length + self.main.gap;
                     }
                 }
 
-                let min_length = state.no_expand_size;
-
-                let extra_width = rect.$primary_size() - min_length;
-                let expand_length = extra_width / state.expand_count as f64;
+                let main = state.main_placement(self, rect);
+                let mut pos = rect.origin();
+                pos.$primary_axis += main.lead;
 
                 let mut handler = KeyboardInputHandler {
                     env,
-                    pos: rect.origin(),
+                    pos,
                     size: rect.size(),
-                    expand_length,
+                    main,
+                    cross: CrossPlacement {
+                        alignment: self.cross_axis_alignment,
+                        baseline: state.baseline,
+                    },
                     input,
                     input_state,
                     theme,
-                    focus: if focus { state.focus } else { None },
-                    i: 0,
+                    focus,
+                    ctx,
                 };
 
                 self.content.all(&mut state.content_state, &mut handler);
             }
+
+            fn accepts_focus(&mut self, state: &mut Self::State, env: &mut E) -> bool {
+                content_accepts_focus(&mut self.content, &mut state.content_state, env)
+            }
+
+            fn move_focus(
+                &mut self,
+                state: &mut Self::State,
+                env: &mut E,
+                direction: FocusDirection,
+                focus: bool,
+                ctx: &mut InputCtx,
+            ) -> bool {
+                content_move_focus(
+                    &mut self.content,
+                    &mut state.content_state,
+                    env,
+                    direction,
+                    focus,
+                    ctx,
+                )
+            }
+
+            fn handle_timer(
+                &mut self,
+                state: &mut Self::State,
+                env: &mut E,
+                token: TimerToken,
+                focus: bool,
+                ctx: &mut InputCtx,
+            ) {
+                content_handle_timer(
+                    &mut self.content,
+                    &mut state.content_state,
+                    env,
+                    token,
+                    focus,
+                    ctx,
+                );
+            }
         }
     };
 }
 
-flex!(Row, RowState, row, x, y, width, height);
-flex!(Col, ColState, col, y, x, height, width);
+flex!(Row, RowState, row, x, y, width, height, true);
+flex!(Col, ColState, col, y, x, height, width, false);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn baseline_placement() {
+        let mut extent = BaselineExtent::default();
+        extent.add(20., Some(15.));
+        extent.add(40., Some(25.));
+        extent.add(10., None);
+        assert_eq!(extent.height(), 25. + 15.);
+
+        let cross = CrossPlacement {
+            alignment: CrossAxisAlignment::Baseline,
+            baseline: extent.ascent,
+        };
+        assert_eq!(cross.place(50., 20., Some(15.)), (10., 20.));
+        assert_eq!(cross.place(50., 10., None), (0., 10.));
+    }
+
+    #[test]
+    fn main_placement() {
+        use MainAxisAlignment::*;
+
+        let place = |alignment| MainPlacement::new(alignment, 100., 40., 0., 5., 3);
+        assert_eq!(place(Start).lead, 0.);
+        assert_eq!(place(Center).lead, 25.);
+        assert_eq!(place(End).lead, 50.);
+        assert_eq!(place(SpaceBetween).gap, 30.);
+        assert_eq!(place(SpaceAround).lead, 50. / 6.);
+        assert_eq!(place(SpaceAround).gap, 5. + 50. / 3.);
+
+        // expanding children take all the space, so there is nothing to align
+        let expand = MainPlacement::new(End, 100., 40., 3., 5., 3);
+        assert_eq!(expand, MainPlacement { lead: 0., gap: 5., flex_unit: 50. / 3. });
+
+        // children that don't fit overflow at the end
+        let overflow = MainPlacement::new(Center, 30., 40., 0., 5., 3);
+        assert_eq!(overflow, MainPlacement { lead: 0., gap: 5., flex_unit: 0. });
+    }
+}
@@ -0,0 +1,846 @@
+use super::*;
+
+/// Builds the content of a [grid] from a list of cells and widgets. A cell is given as
+/// `(column, row)` or as `(column, row, column_span, row_span)`.
+///
+/// ```ignore
+/// grid(
+///     [Track::Auto, Track::Fraction(1.)],
+///     Vec::new(),
+///     grid_content![
+///         (0, 0) => text("Name"),
+///         (1, 0) => textbox(...),
+///         (0, 1, 2, 1) => text_button("Save", ...),
+///     ],
+/// )
+/// ```
+#[macro_export]
+macro_rules! grid_content {
+    (
+        $(($column:expr, $row:expr $(, $column_span:expr, $row_span:expr)?) => $build:expr),*
+        $(,)?
+    ) => {{
+        use $crate::widgets::lists::GridContent;
+        $crate::widgets::lists::EmptyGridContent $(.then(
+            $crate::widgets::lists::GridItem {
+                widget: $build,
+                cell: $crate::widgets::lists::GridCell::new($column, $row)
+                    $(.span($column_span, $row_span))?,
+            }
+        ))*
+    }};
+}
+
+/// How the size of a column or a row of a [Grid] is found.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Track {
+    Fixed(f64),
+    /// As big as the biggest child in it.
+    Auto,
+    /// A share of the space the other tracks leave over, relative to the other fractional tracks.
+    /// Without a limit on the size of the grid this is the same as [Track::Auto].
+    Fraction(f64),
+}
+
+/// The tracks a child of a [Grid] is placed in.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct GridCell {
+    pub column: usize,
+    pub row: usize,
+    // only set through `span`, so they're never zero
+    column_span: usize,
+    row_span: usize,
+}
+
+impl GridCell {
+    pub fn new(column: usize, row: usize) -> Self {
+        GridCell {
+            column,
+            row,
+            column_span: 1,
+            row_span: 1,
+        }
+    }
+
+    /// Makes the cell reach over more than one column or row.
+    pub fn span(mut self, column_span: usize, row_span: usize) -> Self {
+        self.column_span = column_span.max(1);
+        self.row_span = row_span.max(1);
+        self
+    }
+
+    pub fn column_span(&self) -> usize {
+        self.column_span
+    }
+
+    pub fn row_span(&self) -> usize {
+        self.row_span
+    }
+}
+
+pub trait GridContent<E> {
+    type State: FlexContentState;
+
+    fn all<H: GridContentHandler<E>>(&mut self, state: &mut Self::State, handler: &mut H);
+
+    fn then<O: GridContent<E>>(self, other: O) -> Then<Self, O>
+    where
+        Self: Sized,
+    {
+        Then { a: self, b: other }
+    }
+}
+
+pub trait GridContentHandler<E> {
+    /// `focus` and `layout` are the same as for [FlexContentHandler::widget].
+    fn widget<W: Widget<E>>(
+        &mut self,
+        widget: &mut W,
+        state: &mut W::State,
+        cell: GridCell,
+        focus: &mut bool,
+        layout: &mut LayoutCache,
+    );
+}
+
+pub struct GridItem<W> {
+    pub widget: W,
+    pub cell: GridCell,
+}
+
+pub struct GridItemState<S> {
+    state: S,
+    focus: bool,
+    layout: LayoutCache,
+}
+
+impl<E, W: Widget<E>> GridContent<E> for GridItem<W> {
+    type State = GridItemState<W::State>;
+
+    fn all<H: GridContentHandler<E>>(&mut self, state: &mut Self::State, handler: &mut H) {
+        handler.widget(
+            &mut self.widget,
+            &mut state.state,
+            self.cell,
+            &mut state.focus,
+            &mut state.layout,
+        );
+    }
+}
+
+impl<S: WidgetState> FlexContentState for GridItemState<S> {
+    fn new() -> Self {
+        GridItemState {
+            state: S::new(),
+            focus: false,
+            layout: LayoutCache::new(),
+        }
+    }
+}
+
+pub struct EmptyGridContent;
+
+impl<E> GridContent<E> for EmptyGridContent {
+    type State = EmptyFlexContentState;
+
+    fn all<H: GridContentHandler<E>>(&mut self, _: &mut Self::State, _: &mut H) {}
+}
+
+impl<E, A: GridContent<E>, B: GridContent<E>> GridContent<E> for Then<A, B> {
+    type State = ThenState<A::State, B::State>;
+
+    fn all<H: GridContentHandler<E>>(&mut self, state: &mut Self::State, handler: &mut H) {
+        self.a.all(&mut state.a, handler);
+        self.b.all(&mut state.b, handler);
+    }
+}
+
+/// Lets the helpers written for [FlexContent] work on [GridContent] too.
+struct AsFlexContent<'c, C>(&'c mut C);
+
+impl<'c, E, C: GridContent<E>> FlexContent<E> for AsFlexContent<'c, C> {
+    type State = C::State;
+
+    fn all<H: FlexContentHandler<E>>(&mut self, state: &mut Self::State, handler: &mut H) {
+        struct Handler<'h, H>(&'h mut H);
+
+        impl<'h, E, H: FlexContentHandler<E>> GridContentHandler<E> for Handler<'h, H> {
+            fn widget<W: Widget<E>>(
+                &mut self,
+                widget: &mut W,
+                state: &mut W::State,
+                _: GridCell,
+                focus: &mut bool,
+                layout: &mut LayoutCache,
+            ) {
+                self.0.widget(widget, state, 0., focus, layout);
+            }
+        }
+
+        self.0.all(state, &mut Handler(handler));
+    }
+}
+
+/// Places children in columns and rows. Every column is as wide as it needs to be for all the
+/// rows, so the children line up. Children are made as big as their cells.
+///
+/// There are as many columns and rows as the children need. The ones without a [Track] are
+/// [Track::Auto].
+pub fn grid<C>(
+    columns: impl Into<Vec<Track>>,
+    rows: impl Into<Vec<Track>>,
+    content: C,
+) -> Grid<C> {
+    Grid {
+        content,
+        columns: columns.into(),
+        rows: rows.into(),
+        column_spacing: 0.,
+        row_spacing: 0.,
+    }
+}
+
+pub struct Grid<C> {
+    content: C,
+    columns: Vec<Track>,
+    rows: Vec<Track>,
+    column_spacing: f64,
+    row_spacing: f64,
+}
+
+impl<C> Grid<C> {
+    /// The space between two neighbouring columns.
+    pub fn column_spacing(mut self, spacing: f64) -> Self {
+        self.column_spacing = spacing;
+        self
+    }
+
+    /// The space between two neighbouring rows.
+    pub fn row_spacing(mut self, spacing: f64) -> Self {
+        self.row_spacing = spacing;
+        self
+    }
+}
+
+fn track(tracks: &[Track], i: usize) -> Track {
+    tracks.get(i).copied().unwrap_or(Track::Auto)
+}
+
+/// Whether the track gets its size from what the other tracks leave over instead of from its
+/// children.
+fn is_flexible(tracks: &[Track], i: usize, available: f64) -> bool {
+    matches!(track(tracks, i), Track::Fraction(_)) && available.is_finite()
+}
+
+/// Whether a child in `start..start + span` is measured before the tracks are sized, so it can
+/// make them bigger. The others are measured once the size of their tracks is known.
+fn sizes_tracks(tracks: &[Track], start: usize, span: usize, available: f64) -> bool {
+    let mut span = start..start + span;
+
+    !span.clone().any(|i| is_flexible(tracks, i, available))
+        && span.any(|i| !matches!(track(tracks, i), Track::Fixed(_)))
+}
+
+/// Finds the lengths of `count` tracks. `items` are the start, span and length of the children
+/// that [sizes_tracks] is true for.
+fn size_tracks(
+    tracks: &[Track],
+    count: usize,
+    spacing: f64,
+    available: f64,
+    items: &[(usize, usize, f64)],
+) -> Vec<f64> {
+    let mut lengths: Vec<f64> = (0..count)
+        .map(|i| match track(tracks, i) {
+            Track::Fixed(length) => length,
+            _ => 0.,
+        })
+        .collect();
+
+    let grows = |i| !matches!(track(tracks, i), Track::Fixed(_));
+
+    // The children spanning a single track go first, so the wider ones only add what is still
+    // missing. That is shared by all the tracks in the span that can grow.
+    let mut items = items.to_vec();
+    items.sort_by_key(|&(_, span, _)| span);
+
+    for (start, span, length) in items {
+        let growing: Vec<usize> = (start..start + span).filter(|&i| grows(i)).collect();
+        if growing.is_empty() {
+            continue;
+        }
+
+        let current = lengths[start..start + span].iter().sum::<f64>()
+            + spacing * span.saturating_sub(1) as f64;
+        let missing = length - current;
+
+        if missing > 0. {
+            for i in &growing {
+                lengths[*i] += missing / growing.len() as f64;
+            }
+        }
+    }
+
+    let fraction = |i| match track(tracks, i) {
+        Track::Fraction(fraction) if is_flexible(tracks, i, available) => fraction,
+        _ => 0.,
+    };
+    let fraction_total: f64 = (0..count).map(fraction).sum();
+
+    if fraction_total > 0. {
+        let free = available
+            - lengths.iter().sum::<f64>()
+            - spacing * count.saturating_sub(1) as f64;
+
+        for (i, length) in lengths.iter_mut().enumerate() {
+            if fraction(i) > 0. {
+                *length = 0f64.max(free) * fraction(i) / fraction_total;
+            }
+        }
+    }
+
+    lengths
+}
+
+/// The offset and length of every track.
+fn track_positions(lengths: &[f64], spacing: f64) -> Vec<(f64, f64)> {
+    let mut offset = 0.;
+
+    lengths
+        .iter()
+        .map(|&length| {
+            let position = (offset, length);
+            offset += length + spacing;
+            position
+        })
+        .collect()
+}
+
+/// The offset and length of `span` tracks from `start`. A span of zero is treated like one.
+fn span_position(positions: &[(f64, f64)], start: usize, span: usize) -> (f64, f64) {
+    match (positions.get(start), positions.get(start + span.max(1) - 1)) {
+        (Some(&(offset, _)), Some(&(end_offset, end_length))) => {
+            (offset, end_offset + end_length - offset)
+        }
+        // the content changed since the last layout
+        _ => (0., 0.),
+    }
+}
+
+/// Where the columns and rows of a grid ended up.
+#[derive(Default)]
+struct GridTracks {
+    columns: Vec<(f64, f64)>,
+    rows: Vec<(f64, f64)>,
+}
+
+impl GridTracks {
+    fn cell_rect(&self, origin: Point, cell: GridCell) -> Rect {
+        let (x, width) = span_position(&self.columns, cell.column, cell.column_span);
+        let (y, height) = span_position(&self.rows, cell.row, cell.row_span);
+
+        Rect::from_origin_size((origin.x + x, origin.y + y), (width, height))
+    }
+}
+
+pub struct GridState<S> {
+    content_state: S,
+
+    size: Size,
+    tracks: GridTracks,
+    extra_layers: u8,
+}
+
+impl<S: FlexContentState> WidgetState for GridState<S> {
+    fn new() -> Self {
+        GridState {
+            content_state: S::new(),
+            size: Size::ZERO,
+            tracks: GridTracks::default(),
+            extra_layers: 0,
+        }
+    }
+
+    fn min_size(&self) -> Size {
+        self.size
+    }
+
+    fn extra_layers(&self) -> u8 {
+        self.extra_layers
+    }
+}
+
+impl<E, C: GridContent<E>> Widget<E> for Grid<C> {
+    type State = GridState<C::State>;
+
+    fn layout(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        constraint: LayoutConstraint,
+        ctx: &mut LayoutCtx,
+    ) {
+        /// Measures the children the columns are sized by, or once `columns` are known the others.
+        struct MeasureHandler<'a, 'b, 't, E> {
+            env: &'a mut E,
+            ctx: &'a mut LayoutCtx<'b, 't>,
+            tracks: &'a [Track],
+            available: f64,
+            columns: Option<&'a [(f64, f64)]>,
+            children: Vec<(GridCell, Size)>,
+            extra_layers: u8,
+        }
+
+        impl<'a, 'b, 't, E> GridContentHandler<E> for MeasureHandler<'a, 'b, 't, E> {
+            fn widget<W: Widget<E>>(
+                &mut self,
+                widget: &mut W,
+                state: &mut W::State,
+                cell: GridCell,
+                _: &mut bool,
+                layout: &mut LayoutCache,
+            ) {
+                let first =
+                    sizes_tracks(self.tracks, cell.column, cell.column_span, self.available);
+
+                let max_width = match self.columns {
+                    None if first => f64::INFINITY,
+                    Some(columns) if !first => {
+                        span_position(columns, cell.column, cell.column_span).1
+                    }
+                    _ => {
+                        // measured in the other pass
+                        self.children.push((cell, state.min_size()));
+                        return;
+                    }
+                };
+
+                layout.layout(
+                    widget,
+                    state,
+                    self.env,
+                    LayoutConstraint::new(Size::ZERO, Size::new(max_width, f64::INFINITY)),
+                    self.ctx,
+                );
+
+                self.children.push((cell, state.min_size()));
+                self.extra_layers = self.extra_layers.max(state.extra_layers());
+            }
+        }
+
+        let mut handler = MeasureHandler {
+            env: &mut *env,
+            ctx: &mut *ctx,
+            tracks: &self.columns,
+            available: constraint.max.width,
+            columns: None,
+            children: Vec::new(),
+            extra_layers: 0,
+        };
+
+        self.content.all(&mut state.content_state, &mut handler);
+
+        let extra_layers = handler.extra_layers;
+        let children = handler.children;
+
+        let column_count = children
+            .iter()
+            .map(|(cell, _)| cell.column + cell.column_span)
+            .fold(self.columns.len(), usize::max);
+
+        let items: Vec<_> = children
+            .iter()
+            .filter(|(cell, _)| {
+                sizes_tracks(&self.columns, cell.column, cell.column_span, constraint.max.width)
+            })
+            .map(|(cell, size)| (cell.column, cell.column_span, size.width))
+            .collect();
+
+        let column_lengths = size_tracks(
+            &self.columns,
+            column_count,
+            self.column_spacing,
+            constraint.max.width,
+            &items,
+        );
+        let columns = track_positions(&column_lengths, self.column_spacing);
+
+        // now that the columns are known the rest of the children can be measured
+        let mut handler = MeasureHandler {
+            env,
+            ctx,
+            tracks: &self.columns,
+            available: constraint.max.width,
+            columns: Some(&columns),
+            children: Vec::new(),
+            extra_layers,
+        };
+
+        self.content.all(&mut state.content_state, &mut handler);
+
+        let extra_layers = handler.extra_layers;
+        let children = handler.children;
+
+        let row_count = children
+            .iter()
+            .map(|(cell, _)| cell.row + cell.row_span)
+            .fold(self.rows.len(), usize::max);
+
+        let items: Vec<_> = children
+            .iter()
+            .filter(|(cell, _)| {
+                sizes_tracks(&self.rows, cell.row, cell.row_span, constraint.max.height)
+            })
+            .map(|(cell, size)| (cell.row, cell.row_span, size.height))
+            .collect();
+
+        let row_lengths = size_tracks(
+            &self.rows,
+            row_count,
+            self.row_spacing,
+            constraint.max.height,
+            &items,
+        );
+
+        let width = column_lengths.iter().sum::<f64>()
+            + self.column_spacing * column_count.saturating_sub(1) as f64;
+        let height = row_lengths.iter().sum::<f64>()
+            + self.row_spacing * row_count.saturating_sub(1) as f64;
+
+        state.tracks = GridTracks {
+            columns,
+            rows: track_positions(&row_lengths, self.row_spacing),
+        };
+        state.extra_layers = extra_layers;
+        // children that can't be made any smaller might push this beyond the maximum
+        state.size = constraint.constrain_min(Size::new(width, height));
+    }
+
+    fn render(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        layer: u8,
+        focus: bool,
+        ctx: &mut RenderCtx,
+    ) {
+        struct RenderHandler<'a, 'b, 'c, 't, 'is, E> {
+            env: &'a mut E,
+            tracks: &'a GridTracks,
+            origin: Point,
+            layer: u8,
+            extra_layers: u8,
+            focus: bool,
+            ctx: &'a mut RenderCtx<'b, 'c, 't, 'is>,
+        }
+
+        impl<'a, 'b, 'c, 't, 'is, E> GridContentHandler<E>
+            for RenderHandler<'a, 'b, 'c, 't, 'is, E>
+        {
+            fn widget<W: Widget<E>>(
+                &mut self,
+                widget: &mut W,
+                state: &mut W::State,
+                cell: GridCell,
+                focus: &mut bool,
+                _: &mut LayoutCache,
+            ) {
+                if self.layer <= state.extra_layers() {
+                    let empty_input_state: InputState = Default::default();
+
+                    let input_state = if self.layer == self.extra_layers {
+                        self.ctx.input_state
+                    } else {
+                        &empty_input_state
+                    };
+
+                    widget.render(
+                        state,
+                        self.env,
+                        self.tracks.cell_rect(self.origin, cell),
+                        self.layer,
+                        self.focus && *focus,
+                        &mut RenderCtx {
+                            piet: &mut *self.ctx.piet,
+                            input_state: &input_state,
+                            ..*self.ctx
+                        },
+                    );
+                }
+            }
+        }
+
+        let mut handler = RenderHandler {
+            env,
+            tracks: &state.tracks,
+            origin: rect.origin(),
+            layer,
+            extra_layers: state.extra_layers,
+            focus,
+            ctx,
+        };
+
+        self.content.all(&mut state.content_state, &mut handler);
+    }
+
+    fn handle_cursor_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        cursor_pos: Point,
+        cursor_layer: u8,
+        input: CursorInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> InputReturn {
+        struct CursorInputHandler<'a, 'b, E> {
+            env: &'a mut E,
+            tracks: &'a GridTracks,
+            origin: Point,
+            cursor_pos: Point,
+            cursor_layer: u8,
+            theme: &'a Theme,
+            input: CursorInput,
+            input_state: &'a InputState,
+            focus: bool,
+            i: u16,
+            demand_focus: Option<u16>,
+            ctx: &'a mut InputCtx<'b>,
+        }
+
+        impl<'a, 'b, E> GridContentHandler<E> for CursorInputHandler<'a, 'b, E> {
+            fn widget<W: Widget<E>>(
+                &mut self,
+                widget: &mut W,
+                state: &mut W::State,
+                cell: GridCell,
+                focus: &mut bool,
+                layout: &mut LayoutCache,
+            ) {
+                if state.extra_layers() >= self.cursor_layer {
+                    let rect = self.tracks.cell_rect(self.origin, cell);
+                    let child_focus = self.focus && *focus;
+
+                    let ret = layout.track(self.ctx, |ctx| {
+                        widget.handle_cursor_input(
+                            state,
+                            self.env,
+                            rect,
+                            self.cursor_pos,
+                            self.cursor_layer,
+                            self.input,
+                            self.input_state,
+                            self.theme,
+                            child_focus,
+                            ctx,
+                        )
+                    });
+
+                    if ret.demand_focus {
+                        *focus = true;
+                        self.demand_focus = Some(self.i);
+                    }
+                }
+
+                self.i += 1;
+            }
+        }
+
+        let mut handler = CursorInputHandler {
+            env,
+            tracks: &state.tracks,
+            origin: rect.origin(),
+            cursor_pos,
+            cursor_layer,
+            theme,
+            input,
+            input_state,
+            focus,
+            i: 0,
+            demand_focus: None,
+            ctx,
+        };
+
+        self.content.all(&mut state.content_state, &mut handler);
+
+        let demand_focus = handler.demand_focus;
+
+        if demand_focus.is_some() {
+            set_content_focus::<E, _>(
+                &mut AsFlexContent(&mut self.content),
+                &mut state.content_state,
+                demand_focus,
+            );
+        }
+
+        InputReturn {
+            demand_focus: demand_focus.is_some(),
+        }
+    }
+
+    fn handle_keyboard_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input: &KeyboardInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        struct KeyboardInputHandler<'a, 'b, E> {
+            env: &'a mut E,
+            tracks: &'a GridTracks,
+            origin: Point,
+            input: &'a KeyboardInput,
+            input_state: &'a InputState,
+            theme: &'a Theme,
+            focus: bool,
+            ctx: &'a mut InputCtx<'b>,
+        }
+
+        impl<'a, 'b, E> GridContentHandler<E> for KeyboardInputHandler<'a, 'b, E> {
+            fn widget<W: Widget<E>>(
+                &mut self,
+                widget: &mut W,
+                state: &mut W::State,
+                cell: GridCell,
+                focus: &mut bool,
+                layout: &mut LayoutCache,
+            ) {
+                let focus = self.focus && *focus;
+                if focus {
+                    let rect = self.tracks.cell_rect(self.origin, cell);
+
+                    layout.track(self.ctx, |ctx| {
+                        widget.handle_keyboard_input(
+                            state,
+                            self.env,
+                            rect,
+                            self.input,
+                            self.input_state,
+                            self.theme,
+                            focus,
+                            ctx,
+                        )
+                    });
+                }
+            }
+        }
+
+        let mut handler = KeyboardInputHandler {
+            env,
+            tracks: &state.tracks,
+            origin: rect.origin(),
+            input,
+            input_state,
+            theme,
+            focus,
+            ctx,
+        };
+
+        self.content.all(&mut state.content_state, &mut handler);
+    }
+
+    fn accepts_focus(&mut self, state: &mut Self::State, env: &mut E) -> bool {
+        content_accepts_focus(
+            &mut AsFlexContent(&mut self.content),
+            &mut state.content_state,
+            env,
+        )
+    }
+
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        content_move_focus(
+            &mut AsFlexContent(&mut self.content),
+            &mut state.content_state,
+            env,
+            direction,
+            focus,
+            ctx,
+        )
+    }
+
+    fn handle_timer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        content_handle_timer(
+            &mut AsFlexContent(&mut self.content),
+            &mut state.content_state,
+            env,
+            token,
+            focus,
+            ctx,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestHarness;
+    use crate::widgets::drawables::FixedRect;
+
+    #[test]
+    fn track_sizes() {
+        let tracks = [Track::Fixed(10.), Track::Auto, Track::Fraction(1.), Track::Fraction(3.)];
+
+        // the auto track grows to its widest child, the spanning one only adds what's missing
+        let items = [(1, 1, 20.), (1, 1, 30.), (0, 2, 60.)];
+        let lengths = size_tracks(&tracks, 5, 5., 195., &items);
+        assert_eq!(lengths, [10., 45., 30., 90., 0.]);
+
+        // without a limit fractions are sized like auto tracks
+        assert!(sizes_tracks(&tracks, 2, 2, f64::INFINITY));
+        assert!(!sizes_tracks(&tracks, 1, 2, 200.));
+        let lengths = size_tracks(&tracks, 4, 0., f64::INFINITY, &[(3, 1, 25.)]);
+        assert_eq!(lengths, [10., 0., 0., 25.]);
+    }
+
+    #[test]
+    fn span_positions() {
+        let positions = track_positions(&[10., 20., 30.], 5.);
+
+        assert_eq!(span_position(&positions, 1, 2), (15., 55.));
+        assert_eq!(span_position(&positions, 0, 0), (0., 10.));
+        assert_eq!(span_position(&positions, 2, 2), (0., 0.));
+    }
+
+    #[test]
+    fn zero_span_in_the_last_column() {
+        fn rect(width: f64) -> impl Widget<Runtime> {
+            FixedRect(Size::new(width, 10.))
+        }
+
+        let harness = TestHarness::new(
+            grid(
+                [Track::Auto, Track::Auto],
+                Vec::new(),
+                grid_content![
+                    (0, 0) => rect(10.),
+                    (1, 0, 0, 0) => rect(20.),
+                ],
+            ),
+            (100., 100.),
+        );
+
+        assert_eq!(harness.size(), Size::new(30., 10.));
+    }
+}
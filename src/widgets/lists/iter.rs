@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
 use super::*;
 
 pub struct IterFlexContent<I> {
@@ -35,3 +38,117 @@ impl<E, C: FlexContent<E>, I: Iterator<Item = C> + Clone> FlexContent<E> for Ite
         }
     }
 }
+
+/// Like [IterFlexContent], but the state of each child is attached to a key instead of its position.
+/// Inserting, removing or reordering items keeps the state (including focus) with the item it
+/// belongs to, and the state of items whose key disappeared is dropped. Keys have to be unique.
+pub struct KeyedIterFlexContent<I, K, B> {
+    pub iter: I,
+    pub key: K,
+    pub build: B,
+}
+
+pub struct KeyedIterFlexContentState<K, T> {
+    state: HashMap<K, (u64, T)>,
+    generation: u64,
+}
+
+impl<K, T> FlexContentState for KeyedIterFlexContentState<K, T> {
+    fn new() -> Self {
+        Self {
+            state: HashMap::new(),
+            generation: 0,
+        }
+    }
+}
+
+impl<E, T, K, C, I, F, B> FlexContent<E> for KeyedIterFlexContent<I, F, B>
+where
+    K: Hash + Eq,
+    C: FlexContent<E>,
+    I: Iterator<Item = T> + Clone,
+    F: Fn(&T) -> K,
+    B: Fn(T) -> C,
+{
+    type State = KeyedIterFlexContentState<K, C::State>;
+
+    fn all<H: FlexContentHandler<E>>(
+        &mut self,
+        state: &mut Self::State,
+        handler: &mut H,
+    ) {
+        state.generation += 1;
+
+        for item in self.iter.clone() {
+            let key = (self.key)(&item);
+            let mut content = (self.build)(item);
+
+            let (generation, item_state) = state
+                .state
+                .entry(key)
+                .or_insert_with(|| (0, C::State::new()));
+            *generation = state.generation;
+
+            content.all(item_state, handler);
+        }
+
+        let current = state.generation;
+        state.state.retain(|_, (generation, _)| *generation == current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::NoneWidget;
+
+    struct FocusFlags(Vec<bool>, Option<usize>);
+
+    impl FlexContentHandler<()> for FocusFlags {
+        fn widget<W: Widget<()>>(
+            &mut self,
+            _: &mut W,
+            _: &mut W::State,
+            _: f64,
+            focus: &mut bool,
+            _: &mut LayoutCache,
+        ) {
+            if self.1 == Some(self.0.len()) {
+                *focus = true;
+            }
+            self.0.push(*focus);
+        }
+    }
+
+    fn flags(
+        items: &[u32],
+        state: &mut KeyedIterFlexContentState<u32, FlexItemState<crate::widgets::NoneWidgetState>>,
+        focus: Option<usize>,
+    ) -> Vec<bool> {
+        let mut content = KeyedIterFlexContent {
+            iter: items.iter().copied(),
+            key: |item: &u32| *item,
+            build: |_: u32| FlexItem {
+                widget: NoneWidget,
+                flex: 0.,
+            },
+        };
+
+        let mut handler = FocusFlags(Vec::new(), focus);
+        FlexContent::<()>::all(&mut content, state, &mut handler);
+        handler.0
+    }
+
+    #[test]
+    fn state_follows_key() {
+        let mut state = KeyedIterFlexContentState::new();
+
+        assert_eq!(flags(&[1, 2, 3], &mut state, Some(1)), [false, true, false]);
+        assert_eq!(flags(&[3, 1, 2], &mut state, None), [false, false, true]);
+        assert_eq!(flags(&[0, 2], &mut state, None), [false, true]);
+
+        // the state for 2 is dropped when it disappears
+        assert_eq!(flags(&[0], &mut state, None), [false]);
+        assert_eq!(flags(&[0, 2], &mut state, None), [false, false]);
+    }
+}
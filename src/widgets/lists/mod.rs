@@ -5,8 +5,19 @@ use crate::*;
 // pub mod col;
 pub mod iter;
 pub mod flex;
-
-pub use flex::{row, col};
+pub mod grid;
+pub mod stack;
+pub mod virtual_list;
+pub mod wrap;
+
+pub use flex::{row, col, CrossAxisAlignment, MainAxisAlignment};
+pub use grid::{
+    grid, EmptyGridContent, GridCell, GridContent, GridContentHandler, GridItem, GridItemState,
+    Track,
+};
+pub use stack::{aligned, offset, stack, Alignment};
+pub use virtual_list::virtual_list;
+pub use wrap::wrap;
 
 // pub mod iter;
 
@@ -39,20 +50,43 @@ pub use flex::{row, col};
 //     }};
 // }
 
+/// Builds the content of a [row] or [col] from a list of widgets. Every widget expands by default
+/// and they share the space that is left over equally. Prefix a widget with `flex(<factor>) =>` to
+/// give it a bigger or smaller share, or with `fixed =>` to keep it at the size it asks for.
+///
+/// ```ignore
+/// row(flex_content![
+///     fixed => text("Name"),
+///     flex(2.) => textbox(...),
+///     textbox(...),
+/// ])
+/// ```
 #[macro_export]
 macro_rules! flex_content {
-    // () => {
-    //     $crate::widgets::lists::EmptyFlexContent
-    // };
-    ($($build:expr),* $(,)?) => {{
+    (@items $content:expr;) => {
+        $content
+    };
+    (@items $content:expr; fixed => $build:expr $(, $($rest:tt)*)?) => {
+        $crate::flex_content!(@items $content.then($crate::widgets::lists::FlexItem {
+            widget: $build,
+            flex: 0.,
+        }); $($($rest)*)?)
+    };
+    (@items $content:expr; flex($flex:expr) => $build:expr $(, $($rest:tt)*)?) => {
+        $crate::flex_content!(@items $content.then($crate::widgets::lists::FlexItem {
+            widget: $build,
+            flex: $flex,
+        }); $($($rest)*)?)
+    };
+    (@items $content:expr; $build:expr $(, $($rest:tt)*)?) => {
+        $crate::flex_content!(@items $content.then($crate::widgets::lists::FlexItem {
+            widget: $build,
+            flex: 1.,
+        }); $($($rest)*)?)
+    };
+    ($($items:tt)*) => {{
         use $crate::widgets::lists::FlexContent;
-        $crate::widgets::lists::EmptyFlexContent $(.then(
-            // crate::flex_item!($params_var: $params => $build)
-            $crate::widgets::lists::FlexItem {
-                widget: $build,
-                expand: true,
-            }
-        ))*
+        $crate::flex_content!(@items $crate::widgets::lists::EmptyFlexContent; $($items)*)
     }};
 }
 
@@ -74,13 +108,257 @@ pub trait FlexContent<E> {
 }
 
 pub trait FlexContentHandler<E> {
-    fn widget<W: Widget<E>>(&mut self, widget: &mut W, state: &mut W::State, expand: bool);
+    /// `focus` is whether this child is the focused one among its siblings. It lives in the item
+    /// state so it stays with the child when contents reorder their children, just like `layout`
+    /// which is used to skip laying out children that didn't change.
+    fn widget<W: Widget<E>>(
+        &mut self,
+        widget: &mut W,
+        state: &mut W::State,
+        flex: f64,
+        focus: &mut bool,
+        layout: &mut LayoutCache,
+    );
 }
 
 pub trait FlexContentState {
     fn new() -> Self;
 }
 
+/// Whether any child of `content` accepts the focus. Shared by the containers built on
+/// [FlexContent].
+pub fn content_accepts_focus<E, C: FlexContent<E>>(
+    content: &mut C,
+    state: &mut C::State,
+    env: &mut E,
+) -> bool {
+    struct AcceptsFocusHandler<'a, E> {
+        env: &'a mut E,
+        accepts: bool,
+    }
+
+    impl<'a, E> FlexContentHandler<E> for AcceptsFocusHandler<'a, E> {
+        fn widget<W: Widget<E>>(
+            &mut self,
+            widget: &mut W,
+            state: &mut W::State,
+            _: f64,
+            _: &mut bool,
+            _: &mut LayoutCache,
+        ) {
+            self.accepts = self.accepts || widget.accepts_focus(state, self.env);
+        }
+    }
+
+    let mut handler = AcceptsFocusHandler {
+        env,
+        accepts: false,
+    };
+
+    content.all(state, &mut handler);
+
+    handler.accepts
+}
+
+/// Makes the child at index `focused` the focused one, or none of them.
+pub fn set_content_focus<E, C: FlexContent<E>>(
+    content: &mut C,
+    state: &mut C::State,
+    focused: Option<u16>,
+) {
+    struct SetFocusHandler {
+        focused: Option<u16>,
+        i: u16,
+    }
+
+    impl<E> FlexContentHandler<E> for SetFocusHandler {
+        fn widget<W: Widget<E>>(
+            &mut self,
+            _: &mut W,
+            _: &mut W::State,
+            _: f64,
+            focus: &mut bool,
+            _: &mut LayoutCache,
+        ) {
+            *focus = self.focused == Some(self.i);
+            self.i += 1;
+        }
+    }
+
+    content.all(state, &mut SetFocusHandler { focused, i: 0 });
+}
+
+/// Moves the focus between the children of `content`. See [Widget::move_focus].
+pub fn content_move_focus<E, C: FlexContent<E>>(
+    content: &mut C,
+    state: &mut C::State,
+    env: &mut E,
+    direction: FocusDirection,
+    focus: bool,
+    ctx: &mut InputCtx,
+) -> bool {
+    struct FocusableHandler<'a, E> {
+        env: &'a mut E,
+        focusable: Vec<bool>,
+        focused: Option<u16>,
+    }
+
+    impl<'a, E> FlexContentHandler<E> for FocusableHandler<'a, E> {
+        fn widget<W: Widget<E>>(
+            &mut self,
+            widget: &mut W,
+            state: &mut W::State,
+            _: f64,
+            focus: &mut bool,
+            _: &mut LayoutCache,
+        ) {
+            if *focus && self.focused.is_none() {
+                self.focused = Some(self.focusable.len() as u16);
+            }
+
+            self.focusable.push(widget.accepts_focus(state, self.env));
+        }
+    }
+
+    struct MoveFocusHandler<'a, 'b, E> {
+        env: &'a mut E,
+        ctx: &'a mut InputCtx<'b>,
+        target: u16,
+        direction: FocusDirection,
+        focus: bool,
+        i: u16,
+        moved: bool,
+    }
+
+    impl<'a, 'b, E> FlexContentHandler<E> for MoveFocusHandler<'a, 'b, E> {
+        fn widget<W: Widget<E>>(
+            &mut self,
+            widget: &mut W,
+            state: &mut W::State,
+            _: f64,
+            _: &mut bool,
+            layout: &mut LayoutCache,
+        ) {
+            if self.i == self.target {
+                let (env, direction, focus) = (&mut *self.env, self.direction, self.focus);
+                self.moved = layout.track(self.ctx, |ctx| {
+                    widget.move_focus(state, env, direction, focus, ctx)
+                });
+            }
+
+            self.i += 1;
+        }
+    }
+
+    let mut handler = FocusableHandler {
+        env: &mut *env,
+        focusable: Vec::new(),
+        focused: None,
+    };
+
+    content.all(state, &mut handler);
+
+    let focusable = handler.focusable;
+    let current = if focus { handler.focused } else { None };
+
+    // first the focused child gets a chance to move the focus within itself
+    if let Some(current) = current {
+        let mut handler = MoveFocusHandler {
+            env: &mut *env,
+            ctx: &mut *ctx,
+            target: current,
+            direction,
+            focus: true,
+            i: 0,
+            moved: false,
+        };
+
+        content.all(state, &mut handler);
+
+        if handler.moved {
+            return true;
+        }
+    }
+
+    let candidates: Vec<u16> = match direction {
+        FocusDirection::Next => {
+            let start = current.map(|i| i as usize + 1).unwrap_or(0);
+            (start..focusable.len()).map(|i| i as u16).collect()
+        }
+        FocusDirection::Previous => {
+            let end = current.map(|i| i as usize).unwrap_or(focusable.len());
+            (0..end).rev().map(|i| i as u16).collect()
+        }
+    };
+
+    for i in candidates {
+        if !focusable[i as usize] {
+            continue;
+        }
+
+        let mut handler = MoveFocusHandler {
+            env: &mut *env,
+            ctx: &mut *ctx,
+            target: i,
+            direction,
+            focus: false,
+            i: 0,
+            moved: false,
+        };
+
+        content.all(state, &mut handler);
+
+        if handler.moved {
+            set_content_focus::<E, _>(content, state, Some(i));
+            return true;
+        }
+    }
+
+    set_content_focus::<E, _>(content, state, None);
+    false
+}
+
+/// Passes a fired timer on to all children of `content`. See [Widget::handle_timer].
+pub fn content_handle_timer<E, C: FlexContent<E>>(
+    content: &mut C,
+    state: &mut C::State,
+    env: &mut E,
+    token: TimerToken,
+    focus: bool,
+    ctx: &mut InputCtx,
+) {
+    struct TimerHandler<'a, 'b, E> {
+        env: &'a mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &'a mut InputCtx<'b>,
+    }
+
+    impl<'a, 'b, E> FlexContentHandler<E> for TimerHandler<'a, 'b, E> {
+        fn widget<W: Widget<E>>(
+            &mut self,
+            widget: &mut W,
+            state: &mut W::State,
+            _: f64,
+            focus: &mut bool,
+            layout: &mut LayoutCache,
+        ) {
+            let (env, token, focus) = (&mut *self.env, self.token, self.focus && *focus);
+            layout.track(self.ctx, |ctx| widget.handle_timer(state, env, token, focus, ctx));
+        }
+    }
+
+    content.all(
+        state,
+        &mut TimerHandler {
+            env,
+            token,
+            focus,
+            ctx,
+        },
+    );
+}
+
 // pub trait FlexItemBuild {
 //     type Params;
 //     type State;
@@ -91,11 +369,15 @@ pub trait FlexContentState {
 
 pub struct FlexItem<W> {
     pub widget: W,
-    pub expand: bool,
+    /// How much of the space left over by the other children this one gets, relative to the
+    /// siblings that also expand. With `0.` it stays at the size it asks for.
+    pub flex: f64,
 }
 
 pub struct FlexItemState<S> {
     state: S,
+    focus: bool,
+    layout: LayoutCache,
 }
 
 impl<E, W: Widget<E>> FlexContent<E> for FlexItem<W> {
@@ -107,7 +389,13 @@ impl<E, W: Widget<E>> FlexContent<E> for FlexItem<W> {
         handler: &mut H,
     ) {
         // let mut widget = self.build.build(params);
-        handler.widget(&mut self.widget, &mut state.state, self.expand);
+        handler.widget(
+            &mut self.widget,
+            &mut state.state,
+            self.flex,
+            &mut state.focus,
+            &mut state.layout,
+        );
     }
 }
 
@@ -115,6 +403,8 @@ impl<S: WidgetState> FlexContentState for FlexItemState<S> {
     fn new() -> Self {
         FlexItemState {
             state: S::new(),
+            focus: false,
+            layout: LayoutCache::new(),
         }
     }
 }
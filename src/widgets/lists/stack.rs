@@ -0,0 +1,718 @@
+use super::*;
+
+/// Where a child of a [stack] is put when it's smaller than the stack, as fractions of the space
+/// left over on each axis.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Alignment {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Alignment {
+    pub const TOP_LEFT: Alignment = Alignment { x: 0., y: 0. };
+    pub const TOP: Alignment = Alignment { x: 0.5, y: 0. };
+    pub const TOP_RIGHT: Alignment = Alignment { x: 1., y: 0. };
+    pub const LEFT: Alignment = Alignment { x: 0., y: 0.5 };
+    pub const CENTER: Alignment = Alignment { x: 0.5, y: 0.5 };
+    pub const RIGHT: Alignment = Alignment { x: 1., y: 0.5 };
+    pub const BOTTOM_LEFT: Alignment = Alignment { x: 0., y: 1. };
+    pub const BOTTOM: Alignment = Alignment { x: 0.5, y: 1. };
+    pub const BOTTOM_RIGHT: Alignment = Alignment { x: 1., y: 1. };
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Position {
+    Align(Alignment),
+    Offset(Vec2),
+}
+
+/// Keeps `widget` at the size it asks for and puts it at `alignment` in the rect it's given, like a
+/// badge in the corner of a [stack].
+pub fn aligned<W>(alignment: Alignment, widget: W) -> Positioned<W> {
+    Positioned {
+        widget,
+        position: Position::Align(alignment),
+    }
+}
+
+/// Keeps `widget` at the size it asks for and puts it at `offset` from the top left of the rect
+/// it's given.
+pub fn offset<W>(offset: Vec2, widget: W) -> Positioned<W> {
+    Positioned {
+        widget,
+        position: Position::Offset(offset),
+    }
+}
+
+pub struct Positioned<W> {
+    widget: W,
+    position: Position,
+}
+
+impl<W> Positioned<W> {
+    fn child_rect(&self, rect: Rect, size: Size) -> Rect {
+        let origin = match self.position {
+            Position::Align(alignment) => Point::new(
+                rect.x0 + (rect.width() - size.width) * alignment.x,
+                rect.y0 + (rect.height() - size.height) * alignment.y,
+            ),
+            Position::Offset(offset) => rect.origin() + offset,
+        };
+
+        Rect::from_origin_size(origin, size)
+    }
+}
+
+pub struct PositionedState<S> {
+    state: S,
+    min_size: Size,
+}
+
+impl<S: WidgetState> WidgetState for PositionedState<S> {
+    fn new() -> Self {
+        PositionedState {
+            state: S::new(),
+            min_size: Size::ZERO,
+        }
+    }
+
+    fn min_size(&self) -> Size {
+        self.min_size
+    }
+
+    fn extra_layers(&self) -> u8 {
+        self.state.extra_layers()
+    }
+}
+
+impl<E, W: Widget<E>> Widget<E> for Positioned<W> {
+    type State = PositionedState<W::State>;
+
+    fn layout(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        constraint: LayoutConstraint,
+        ctx: &mut LayoutCtx,
+    ) {
+        let offset = match self.position {
+            Position::Align(_) => Size::ZERO,
+            Position::Offset(offset) => Size::new(offset.x, offset.y),
+        };
+
+        self.widget
+            .layout(&mut state.state, env, constraint.loosen().deflate(offset), ctx);
+
+        state.min_size = constraint.constrain_min(state.state.min_size() + offset);
+    }
+
+    fn render(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        layer: u8,
+        focus: bool,
+        ctx: &mut RenderCtx,
+    ) {
+        let child_rect = self.child_rect(rect, state.state.min_size());
+        self.widget
+            .render(&mut state.state, env, child_rect, layer, focus, ctx);
+    }
+
+    fn test_input_pos_layer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input_pos: Point,
+    ) -> Option<u8> {
+        let child_rect = self.child_rect(rect, state.state.min_size());
+        self.widget
+            .test_input_pos_layer(&mut state.state, env, child_rect, input_pos)
+    }
+
+    fn handle_cursor_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        cursor_pos: Point,
+        cursor_layer: u8,
+        input: CursorInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> InputReturn {
+        let child_rect = self.child_rect(rect, state.state.min_size());
+        self.widget.handle_cursor_input(
+            &mut state.state,
+            env,
+            child_rect,
+            cursor_pos,
+            cursor_layer,
+            input,
+            input_state,
+            theme,
+            focus,
+            ctx,
+        )
+    }
+
+    fn handle_keyboard_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input: &KeyboardInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        let child_rect = self.child_rect(rect, state.state.min_size());
+        self.widget.handle_keyboard_input(
+            &mut state.state,
+            env,
+            child_rect,
+            input,
+            input_state,
+            theme,
+            focus,
+            ctx,
+        );
+    }
+
+    fn accepts_focus(&mut self, state: &mut Self::State, env: &mut E) -> bool {
+        self.widget.accepts_focus(&mut state.state, env)
+    }
+
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        self.widget
+            .move_focus(&mut state.state, env, direction, focus, ctx)
+    }
+
+    fn handle_timer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        self.widget
+            .handle_timer(&mut state.state, env, token, focus, ctx);
+    }
+}
+
+/// Puts all the children on top of each other in the same rect, the later ones above the earlier
+/// ones. They fill the whole stack unless they are wrapped in [aligned] or [offset]. The flex
+/// factors of the items don't matter here.
+///
+/// Only the topmost child under the cursor gets cursor input and is drawn hovered, so overlays
+/// cover what's below them. Popups still go above all of them.
+pub fn stack<C>(content: C) -> Stack<C> {
+    Stack { content }
+}
+
+pub struct Stack<C> {
+    content: C,
+}
+
+pub struct StackState<S> {
+    content_state: S,
+
+    size: Size,
+    extra_layers: u8,
+    /// The child that was under the cursor at the last cursor input.
+    hovered: Option<u16>,
+    /// The child that got the last mouse button press. It keeps getting the cursor input until
+    /// the button is released, even when something else is above it by then.
+    pressed: Option<u16>,
+}
+
+impl<S: FlexContentState> WidgetState for StackState<S> {
+    fn new() -> Self {
+        StackState {
+            content_state: S::new(),
+            size: Size::ZERO,
+            extra_layers: 0,
+            hovered: None,
+            pressed: None,
+        }
+    }
+
+    fn min_size(&self) -> Size {
+        self.size
+    }
+
+    fn extra_layers(&self) -> u8 {
+        self.extra_layers
+    }
+}
+
+/// Finds the index and the layer of the topmost child of `content` that `pos` hits. Children on a
+/// higher layer are above the others, and within a layer the later children are.
+fn hit_child<E, C: FlexContent<E>>(
+    content: &mut C,
+    state: &mut C::State,
+    env: &mut E,
+    rect: Rect,
+    pos: Point,
+) -> Option<(u16, u8)> {
+    struct HitHandler<'a, E> {
+        env: &'a mut E,
+        rect: Rect,
+        pos: Point,
+        i: u16,
+        hit: Option<(u16, u8)>,
+    }
+
+    impl<'a, E> FlexContentHandler<E> for HitHandler<'a, E> {
+        fn widget<W: Widget<E>>(
+            &mut self,
+            widget: &mut W,
+            state: &mut W::State,
+            _: f64,
+            _: &mut bool,
+            _: &mut LayoutCache,
+        ) {
+            if let Some(layer) = widget.test_input_pos_layer(state, self.env, self.rect, self.pos) {
+                if self.hit.map_or(true, |(_, hit_layer)| layer >= hit_layer) {
+                    self.hit = Some((self.i, layer));
+                }
+            }
+
+            self.i += 1;
+        }
+    }
+
+    let mut handler = HitHandler {
+        env,
+        rect,
+        pos,
+        i: 0,
+        hit: None,
+    };
+
+    content.all(state, &mut handler);
+
+    handler.hit
+}
+
+impl<E, C: FlexContent<E>> Widget<E> for Stack<C> {
+    type State = StackState<C::State>;
+
+    fn layout(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        constraint: LayoutConstraint,
+        ctx: &mut LayoutCtx,
+    ) {
+        struct MeasureHandler<'a, 'b, 't, E> {
+            env: &'a mut E,
+            constraint: LayoutConstraint,
+            ctx: &'a mut LayoutCtx<'b, 't>,
+            size: Size,
+            extra_layers: u8,
+        }
+
+        impl<'a, 'b, 't, E> FlexContentHandler<E> for MeasureHandler<'a, 'b, 't, E> {
+            fn widget<W: Widget<E>>(
+                &mut self,
+                widget: &mut W,
+                state: &mut W::State,
+                _: f64,
+                _: &mut bool,
+                layout: &mut LayoutCache,
+            ) {
+                layout.layout(widget, state, self.env, self.constraint, self.ctx);
+
+                let min_size = state.min_size();
+                self.size = Size::new(
+                    self.size.width.max(min_size.width),
+                    self.size.height.max(min_size.height),
+                );
+                self.extra_layers = self.extra_layers.max(state.extra_layers());
+            }
+        }
+
+        let mut handler = MeasureHandler {
+            env,
+            constraint,
+            ctx,
+            size: Size::ZERO,
+            extra_layers: 0,
+        };
+
+        self.content.all(&mut state.content_state, &mut handler);
+
+        state.size = constraint.constrain_min(handler.size);
+        state.extra_layers = handler.extra_layers;
+    }
+
+    fn render(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        layer: u8,
+        focus: bool,
+        ctx: &mut RenderCtx,
+    ) {
+        struct RenderHandler<'a, 'b, 'c, 't, 'is, E> {
+            env: &'a mut E,
+            rect: Rect,
+            layer: u8,
+            hovered: Option<u16>,
+            focus: bool,
+            i: u16,
+            ctx: &'a mut RenderCtx<'b, 'c, 't, 'is>,
+        }
+
+        impl<'a, 'b, 'c, 't, 'is, E> FlexContentHandler<E>
+            for RenderHandler<'a, 'b, 'c, 't, 'is, E>
+        {
+            fn widget<W: Widget<E>>(
+                &mut self,
+                widget: &mut W,
+                state: &mut W::State,
+                _: f64,
+                focus: &mut bool,
+                _: &mut LayoutCache,
+            ) {
+                if self.layer <= state.extra_layers() {
+                    let empty_input_state: InputState = Default::default();
+
+                    // the children below the one under the cursor are covered by it
+                    let input_state = if self.hovered == Some(self.i) {
+                        self.ctx.input_state
+                    } else {
+                        &empty_input_state
+                    };
+
+                    widget.render(
+                        state,
+                        self.env,
+                        self.rect,
+                        self.layer,
+                        self.focus && *focus,
+                        &mut RenderCtx {
+                            piet: &mut *self.ctx.piet,
+                            input_state: &input_state,
+                            ..*self.ctx
+                        },
+                    );
+                }
+
+                self.i += 1;
+            }
+        }
+
+        let hovered = ctx.input_state.cursor_pos.and_then(|pos| {
+            hit_child(&mut self.content, &mut state.content_state, env, rect, pos)
+        });
+
+        let mut handler = RenderHandler {
+            env,
+            rect,
+            layer,
+            hovered: hovered.map(|(i, _)| i),
+            focus,
+            i: 0,
+            ctx,
+        };
+
+        self.content.all(&mut state.content_state, &mut handler);
+    }
+
+    fn test_input_pos_layer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input_pos: Point,
+    ) -> Option<u8> {
+        match hit_child(&mut self.content, &mut state.content_state, env, rect, input_pos) {
+            Some((_, layer)) => Some(layer),
+            None if rect.contains(input_pos) => Some(0),
+            None => None,
+        }
+    }
+
+    fn handle_cursor_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        cursor_pos: Point,
+        cursor_layer: u8,
+        input: CursorInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> InputReturn {
+        struct CursorInputHandler<'a, 'b, E> {
+            env: &'a mut E,
+            rect: Rect,
+            target: u16,
+            cursor_pos: Point,
+            cursor_layer: u8,
+            theme: &'a Theme,
+            input: CursorInput,
+            input_state: &'a InputState,
+            focus: bool,
+            i: u16,
+            demand_focus: bool,
+            ctx: &'a mut InputCtx<'b>,
+        }
+
+        impl<'a, 'b, E> FlexContentHandler<E> for CursorInputHandler<'a, 'b, E> {
+            fn widget<W: Widget<E>>(
+                &mut self,
+                widget: &mut W,
+                state: &mut W::State,
+                _: f64,
+                focus: &mut bool,
+                layout: &mut LayoutCache,
+            ) {
+                if self.i == self.target {
+                    let child_focus = self.focus && *focus;
+
+                    let ret = layout.track(self.ctx, |ctx| {
+                        widget.handle_cursor_input(
+                            state,
+                            self.env,
+                            self.rect,
+                            self.cursor_pos,
+                            self.cursor_layer,
+                            self.input,
+                            self.input_state,
+                            self.theme,
+                            child_focus,
+                            ctx,
+                        )
+                    });
+
+                    if ret.demand_focus {
+                        *focus = true;
+                        self.demand_focus = true;
+                    }
+                }
+
+                self.i += 1;
+            }
+        }
+
+        let hit = hit_child(&mut self.content, &mut state.content_state, env, rect, cursor_pos)
+            .filter(|&(_, layer)| layer >= cursor_layer)
+            .map(|(i, _)| i);
+
+        // the hover highlight moves from one child to another
+        if hit != state.hovered {
+            state.hovered = hit;
+            ctx.invalidate_rect(rect);
+        }
+
+        let target = match input {
+            CursorInput::Down(..) => {
+                state.pressed = hit;
+                hit
+            }
+            CursorInput::Up(..) => state.pressed.take().or(hit),
+            _ => state.pressed.or(hit),
+        };
+
+        let target = match target {
+            Some(target) => target,
+            None => return Default::default(),
+        };
+
+        let mut handler = CursorInputHandler {
+            env,
+            rect,
+            target,
+            cursor_pos,
+            cursor_layer,
+            theme,
+            input,
+            input_state,
+            focus,
+            i: 0,
+            demand_focus: false,
+            ctx,
+        };
+
+        self.content.all(&mut state.content_state, &mut handler);
+
+        let demand_focus = handler.demand_focus;
+
+        if demand_focus {
+            set_content_focus::<E, _>(&mut self.content, &mut state.content_state, Some(target));
+        }
+
+        InputReturn { demand_focus }
+    }
+
+    fn handle_keyboard_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input: &KeyboardInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        struct KeyboardInputHandler<'a, 'b, E> {
+            env: &'a mut E,
+            rect: Rect,
+            input: &'a KeyboardInput,
+            input_state: &'a InputState,
+            theme: &'a Theme,
+            focus: bool,
+            ctx: &'a mut InputCtx<'b>,
+        }
+
+        impl<'a, 'b, E> FlexContentHandler<E> for KeyboardInputHandler<'a, 'b, E> {
+            fn widget<W: Widget<E>>(
+                &mut self,
+                widget: &mut W,
+                state: &mut W::State,
+                _: f64,
+                focus: &mut bool,
+                layout: &mut LayoutCache,
+            ) {
+                let focus = self.focus && *focus;
+                if focus {
+                    let rect = self.rect;
+
+                    layout.track(self.ctx, |ctx| {
+                        widget.handle_keyboard_input(
+                            state,
+                            self.env,
+                            rect,
+                            self.input,
+                            self.input_state,
+                            self.theme,
+                            focus,
+                            ctx,
+                        )
+                    });
+                }
+            }
+        }
+
+        let mut handler = KeyboardInputHandler {
+            env,
+            rect,
+            input,
+            input_state,
+            theme,
+            focus,
+            ctx,
+        };
+
+        self.content.all(&mut state.content_state, &mut handler);
+    }
+
+    fn accepts_focus(&mut self, state: &mut Self::State, env: &mut E) -> bool {
+        content_accepts_focus(&mut self.content, &mut state.content_state, env)
+    }
+
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        content_move_focus(
+            &mut self.content,
+            &mut state.content_state,
+            env,
+            direction,
+            focus,
+            ctx,
+        )
+    }
+
+    fn handle_timer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        content_handle_timer(
+            &mut self.content,
+            &mut state.content_state,
+            env,
+            token,
+            focus,
+            ctx,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flex_content;
+    use crate::testing::TestHarness;
+    use crate::widgets::button::text_button;
+
+    fn badge_over_button() -> impl Widget<[u32; 2]> {
+        stack(flex_content![
+            text_button("Below", |clicks: &mut [u32; 2]| clicks[0] += 1),
+            aligned(
+                Alignment::TOP_LEFT,
+                text_button("Badge", |clicks: &mut [u32; 2]| clicks[1] += 1),
+            ),
+        ])
+    }
+
+    #[test]
+    fn only_the_top_child_clicks() {
+        let mut harness = TestHarness::with_env(badge_over_button(), [0, 0], (200., 100.));
+
+        // both buttons are under the cursor, but the badge covers the other one
+        harness.click((5., 5.));
+        assert_eq!(*harness.env(), [0, 1]);
+
+        harness.click((195., 95.));
+        assert_eq!(*harness.env(), [1, 1]);
+    }
+
+    #[test]
+    fn press_stays_on_its_child() {
+        let mut harness = TestHarness::with_env(badge_over_button(), [0, 0], (200., 100.));
+
+        // the button below doesn't get the release of a press that started on the badge
+        harness.move_to((5., 5.));
+        harness.mouse_down((5., 5.), MouseButton::Primary);
+        harness.move_to((195., 95.));
+        harness.mouse_up((195., 95.), MouseButton::Primary);
+        assert_eq!(*harness.env(), [0, 0]);
+
+        // and the badge gets the release of its own press even after the cursor left it
+        harness.mouse_down((5., 5.), MouseButton::Primary);
+        harness.move_to((195., 95.));
+        harness.move_to((5., 5.));
+        harness.mouse_up((5., 5.), MouseButton::Primary);
+        assert_eq!(*harness.env(), [0, 1]);
+    }
+}
@@ -0,0 +1,696 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use druid_shell::kurbo::{Point, Rect, Size};
+
+use super::*;
+use crate::widgets::scroll::{
+    render_thumb, thumb_rect, thumb_start_to_offset, Axis, SCROLLBAR_WIDTH,
+};
+use crate::widgets::OUTSIDE;
+
+/// The height of the rows of a [virtual_list]. A plain `f64` makes every row exactly that high. A
+/// `Fn(usize) -> f64` only estimates the height of each row until it has been laid out once.
+pub trait RowHeight {
+    fn estimate(&self, index: usize) -> f64;
+
+    /// Whether every row is exactly as high as its estimate. Rows then get their height as a
+    /// constraint and are never measured.
+    fn is_exact(&self) -> bool {
+        false
+    }
+}
+
+impl RowHeight for f64 {
+    fn estimate(&self, _: usize) -> f64 {
+        *self
+    }
+
+    fn is_exact(&self) -> bool {
+        true
+    }
+}
+
+impl<F: Fn(usize) -> f64> RowHeight for F {
+    fn estimate(&self, index: usize) -> f64 {
+        self(index)
+    }
+}
+
+/// A vertical list of `count` rows that scrolls on its own. Only the rows intersecting the viewport
+/// get built (through `build_row`), laid out and rendered, and only their state (plus the state of
+/// the focused row) is kept around.
+///
+/// This does its own scrolling, so it should get a height constraint instead of being put in a
+/// [scroll](crate::widgets::scroll::scroll).
+pub fn virtual_list<E, H: RowHeight, W: Widget<E>, B: Fn(usize) -> W>(
+    count: usize,
+    row_height: H,
+    build_row: B,
+) -> impl Widget<E, State = VirtualListState<W::State>> {
+    VirtualList {
+        count,
+        row_height,
+        build_row,
+    }
+}
+
+pub struct VirtualList<H, B> {
+    count: usize,
+    row_height: H,
+    build_row: B,
+}
+
+pub struct VirtualListState<S> {
+    rows: HashMap<usize, S>,
+    /// The measured or estimated height of every row.
+    heights: Vec<f64>,
+    /// The top of every row relative to the top of the content followed by the height of the
+    /// content, summed up from `heights` whenever they change.
+    tops: Vec<f64>,
+    offset: f64,
+    viewport_height: f64,
+    visible: Range<usize>,
+    /// The top of the first visible row relative to the top of the content.
+    visible_top: f64,
+    focused: Option<usize>,
+    /// Set while the scrollbar thumb is being dragged, to where on the thumb it was grabbed.
+    drag: Option<f64>,
+    min_size: Size,
+    extra_layers: u8,
+}
+
+impl<S> VirtualListState<S> {
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// The rows that were laid out in the last layout pass.
+    pub fn visible_rows(&self) -> Range<usize> {
+        self.visible.clone()
+    }
+
+    /// The number of rows that currently have a state.
+    pub fn live_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn content_height(&self) -> f64 {
+        *self.tops.last().unwrap()
+    }
+
+    fn row_top(&self, index: usize) -> f64 {
+        self.tops[index]
+    }
+
+    fn update_tops(&mut self) {
+        let mut top = 0.;
+
+        self.tops.clear();
+        self.tops.push(top);
+
+        for height in &self.heights {
+            top += height;
+            self.tops.push(top);
+        }
+    }
+
+    /// Drops the state of the rows that are neither visible nor focused.
+    fn forget_hidden_rows(&mut self) {
+        let visible = self.visible.clone();
+        let focused = self.focused;
+
+        self.rows
+            .retain(|i, _| visible.contains(i) || Some(*i) == focused);
+    }
+
+    /// Scrolls by the wheel `delta` unless a row already scrolled because of it, and only takes
+    /// the wheel if the offset actually changed.
+    fn scroll_wheel(&mut self, rect: Rect, delta: f64, ctx: &mut InputCtx) {
+        if ctx.wheel_handled {
+            return;
+        }
+
+        let old_offset = self.offset;
+
+        self.offset += delta;
+        self.clamp_offset();
+
+        if self.offset != old_offset {
+            ctx.wheel_handled = true;
+            ctx.invalidate_rect(rect);
+            // which rows are built depends on the offset
+            ctx.request_layout();
+        }
+    }
+
+    fn clamp_offset(&mut self) {
+        let max_offset = (self.content_height() - self.viewport_height).max(0.);
+        self.offset = self.offset.max(0.).min(max_offset);
+    }
+
+    /// Scrolls just far enough for the given row to be fully visible.
+    fn scroll_to_row(&mut self, index: usize) {
+        let top = self.row_top(index);
+        let bottom = top + self.heights[index];
+
+        if top < self.offset {
+            self.offset = top;
+        } else if bottom > self.offset + self.viewport_height {
+            self.offset = bottom - self.viewport_height;
+        }
+
+        self.clamp_offset();
+    }
+}
+
+impl<S: WidgetState> WidgetState for VirtualListState<S> {
+    fn new() -> Self {
+        VirtualListState {
+            rows: HashMap::new(),
+            heights: Vec::new(),
+            tops: vec![0.],
+            offset: 0.,
+            viewport_height: 0.,
+            visible: 0..0,
+            visible_top: 0.,
+            focused: None,
+            drag: None,
+            min_size: Size::ZERO,
+            extra_layers: 0,
+        }
+    }
+
+    fn min_size(&self) -> Size {
+        self.min_size
+    }
+
+    fn extra_layers(&self) -> u8 {
+        self.extra_layers
+    }
+}
+
+fn viewport(rect: Rect) -> Rect {
+    Rect::new(
+        rect.x0,
+        rect.y0,
+        (rect.x1 - SCROLLBAR_WIDTH).max(rect.x0),
+        rect.y1,
+    )
+}
+
+fn track(rect: Rect) -> Rect {
+    let viewport = viewport(rect);
+    Rect::new(viewport.x1, viewport.y0, rect.x1, viewport.y1)
+}
+
+impl<H, B> VirtualList<H, B> {
+    /// Calls `f` for every visible row that has a state, with the rect it is shown in.
+    fn visible_rows<E, W: Widget<E>>(
+        &self,
+        state: &mut VirtualListState<W::State>,
+        rect: Rect,
+        mut f: impl FnMut(usize, &mut W, &mut W::State, Rect),
+    ) where
+        B: Fn(usize) -> W,
+    {
+        let viewport = viewport(rect);
+        let mut top = viewport.y0 + state.visible_top - state.offset;
+
+        for i in state.visible.clone() {
+            let height = state.heights[i];
+
+            if let Some(row_state) = state.rows.get_mut(&i) {
+                let mut widget = (self.build_row)(i);
+                let row_rect = Rect::new(viewport.x0, top, viewport.x1, top + height);
+                f(i, &mut widget, row_state, row_rect);
+            }
+
+            top += height;
+        }
+    }
+}
+
+impl<E, H: RowHeight, W: Widget<E>, B: Fn(usize) -> W> Widget<E> for VirtualList<H, B> {
+    type State = VirtualListState<W::State>;
+
+    fn layout(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        constraint: LayoutConstraint,
+        ctx: &mut LayoutCtx,
+    ) {
+        let count = self.count;
+        let exact = self.row_height.is_exact();
+
+        state.heights.truncate(count);
+        while state.heights.len() < count {
+            let estimate = self.row_height.estimate(state.heights.len());
+            state.heights.push(estimate);
+        }
+
+        if exact {
+            for (i, height) in state.heights.iter_mut().enumerate() {
+                *height = self.row_height.estimate(i);
+            }
+        }
+
+        state.update_tops();
+
+        if state.focused.map_or(false, |i| i >= count) {
+            state.focused = None;
+        }
+
+        // all the height we may have is filled with rows, since we'd be stretched to it anyway
+        state.viewport_height = if constraint.is_height_bounded() {
+            constraint.max.height
+        } else {
+            state.content_height().max(constraint.min.height)
+        };
+        state.clamp_offset();
+
+        let row_constraint = constraint.deflate(Size::new(SCROLLBAR_WIDTH, 0.));
+
+        // the first row that isn't entirely above the viewport
+        let first = state.tops[1..].partition_point(|&bottom| bottom <= state.offset);
+        let mut i = first;
+        let mut top = state.tops[first];
+
+        state.visible_top = top;
+
+        let mut width: f64 = 0.;
+        let mut extra_layers = 0;
+
+        while i < count && (top < state.offset + state.viewport_height || i == first) {
+            let mut widget = (self.build_row)(i);
+            let row_state = state.rows.entry(i).or_insert_with(W::State::new);
+
+            widget.layout(
+                row_state,
+                env,
+                LayoutConstraint {
+                    min: Size::new(
+                        row_constraint.min.width,
+                        if exact { state.heights[i] } else { 0. },
+                    ),
+                    max: Size::new(
+                        row_constraint.max.width,
+                        if exact { state.heights[i] } else { f64::INFINITY },
+                    ),
+                },
+                ctx,
+            );
+
+            let size = row_state.min_size();
+            width = width.max(size.width);
+            extra_layers = extra_layers.max(row_state.extra_layers());
+
+            if !exact {
+                state.heights[i] = size.height;
+            }
+
+            top += state.heights[i];
+            i += 1;
+        }
+
+        state.visible = first..i;
+        state.update_tops();
+        state.forget_hidden_rows();
+
+        state.extra_layers = extra_layers;
+        state.min_size =
+            constraint.constrain(Size::new(width + SCROLLBAR_WIDTH, state.viewport_height));
+    }
+
+    fn render(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        layer: u8,
+        focus: bool,
+        ctx: &mut RenderCtx,
+    ) {
+        let viewport = viewport(rect);
+        let focused = state.focused;
+        let extra_layers = state.extra_layers;
+
+        let empty_input_state: InputState = Default::default();
+
+        let mut input_state = if layer == extra_layers {
+            ctx.input_state.clone()
+        } else {
+            empty_input_state
+        };
+
+        if layer == 0 {
+            if !input_state.cursor_pos.map_or(false, |p| viewport.contains(p)) {
+                input_state.cursor_pos = None;
+            }
+
+            ctx.piet.save().unwrap();
+            ctx.piet.clip(viewport);
+        }
+
+        self.visible_rows::<E, W>(state, rect, |i, widget: &mut W, row_state, row_rect| {
+            if layer <= row_state.extra_layers() {
+                widget.render(
+                    row_state,
+                    env,
+                    row_rect,
+                    layer,
+                    focus && focused == Some(i),
+                    &mut RenderCtx {
+                        piet: &mut *ctx.piet,
+                        theme: ctx.theme,
+                        input_state: &input_state,
+                    },
+                );
+            }
+        });
+
+        if layer == 0 {
+            ctx.piet.restore().unwrap();
+
+            if let Some(thumb) = thumb_rect(
+                track(rect),
+                Axis::Vertical,
+                state.content_height(),
+                state.offset,
+            ) {
+                render_thumb(thumb, state.drag.is_some(), ctx);
+            }
+        }
+    }
+
+    fn handle_cursor_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        mut cursor_pos: Point,
+        cursor_layer: u8,
+        input: CursorInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> InputReturn {
+        if cursor_layer == 0 {
+            let track = track(rect);
+            let thumb = thumb_rect(
+                track,
+                Axis::Vertical,
+                state.content_height(),
+                state.offset,
+            );
+
+            match input {
+                CursorInput::Down(MouseButton::Primary)
+                    if thumb.map_or(false, |t| t.contains(cursor_pos)) =>
+                {
+                    state.drag = Some(cursor_pos.y - thumb.unwrap().y0);
+                    ctx.invalidate_rect(rect);
+
+                    return Default::default();
+                }
+                CursorInput::Move => {
+                    if let Some(grab) = state.drag {
+                        state.offset = thumb_start_to_offset(
+                            track,
+                            Axis::Vertical,
+                            state.content_height(),
+                            cursor_pos.y - grab,
+                        );
+                        ctx.invalidate_rect(rect);
+                        ctx.request_layout();
+
+                        return Default::default();
+                    }
+                }
+                CursorInput::Up(..) => {
+                    if state.drag.take().is_some() {
+                        ctx.invalidate_rect(rect);
+                        return Default::default();
+                    }
+                }
+                _ => (),
+            }
+
+            if !viewport(rect).contains(cursor_pos) {
+                // the wheel still scrolls over the scrollbar
+                if let CursorInput::Wheel(delta) = input {
+                    if rect.contains(cursor_pos) {
+                        state.scroll_wheel(rect, delta.y, ctx);
+                    }
+                }
+
+                // A press in a row has to end even if it is released out here, but no row may
+                // count the release as being over it.
+                if !matches!(input, CursorInput::Up(..)) {
+                    return Default::default();
+                }
+
+                cursor_pos = OUTSIDE;
+            }
+        }
+
+        let focused = state.focused;
+        let mut demand_focus = None;
+
+        self.visible_rows::<E, W>(state, rect, |i, widget: &mut W, row_state, row_rect| {
+            if row_state.extra_layers() >= cursor_layer {
+                let ret = widget.handle_cursor_input(
+                    row_state,
+                    env,
+                    row_rect,
+                    cursor_pos,
+                    cursor_layer,
+                    input,
+                    input_state,
+                    theme,
+                    focus && focused == Some(i),
+                    ctx,
+                );
+
+                if ret.demand_focus {
+                    demand_focus = Some(i);
+                }
+            }
+        });
+
+        if demand_focus.is_some() {
+            state.focused = demand_focus;
+        }
+
+        // the rows get the wheel first, so scroll areas in them scroll before the list does
+        if let CursorInput::Wheel(delta) = input {
+            if cursor_layer == 0 {
+                state.scroll_wheel(rect, delta.y, ctx);
+            }
+        }
+
+        InputReturn {
+            demand_focus: demand_focus.is_some(),
+        }
+    }
+
+    fn handle_keyboard_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input: &KeyboardInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        let focused = match state.focused {
+            Some(focused) if focus => focused,
+            _ => return,
+        };
+
+        let viewport = viewport(rect);
+        let top = viewport.y0 + state.row_top(focused) - state.offset;
+        let row_rect = Rect::new(viewport.x0, top, viewport.x1, top + state.heights[focused]);
+
+        if let Some(row_state) = state.rows.get_mut(&focused) {
+            (self.build_row)(focused).handle_keyboard_input(
+                row_state,
+                env,
+                row_rect,
+                input,
+                input_state,
+                theme,
+                true,
+                ctx,
+            );
+        }
+    }
+
+    /// Only the rows that were built are asked, the others may not exist for a long time.
+    fn accepts_focus(&mut self, state: &mut Self::State, env: &mut E) -> bool {
+        state
+            .rows
+            .iter_mut()
+            .any(|(&i, row_state)| (self.build_row)(i).accepts_focus(row_state, env))
+    }
+
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        let current = if focus { state.focused } else { None };
+
+        // first the focused row gets a chance to move the focus within itself
+        if let Some(current) = current {
+            if let Some(row_state) = state.rows.get_mut(&current) {
+                if (self.build_row)(current).move_focus(row_state, env, direction, true, ctx) {
+                    return true;
+                }
+            }
+        }
+
+        let step = |i: usize| match direction {
+            FocusDirection::Next => i.checked_add(1),
+            FocusDirection::Previous => i.checked_sub(1),
+        };
+
+        let mut next = match (current, direction) {
+            (Some(current), _) => step(current),
+            (None, FocusDirection::Next) => Some(0),
+            (None, FocusDirection::Previous) => self.count.checked_sub(1),
+        };
+
+        // Rows that weren't built yet are only searched for one viewport height, like a page
+        // scrolled by the user, instead of building every row of a possibly huge list.
+        let search_start = next.filter(|i| *i < self.count).map(|i| state.row_top(i));
+
+        while let Some(i) = next.filter(|i| *i < self.count) {
+            let distance = (state.row_top(i) - search_start.unwrap()).abs();
+
+            if distance > state.viewport_height && !state.rows.contains_key(&i) {
+                break;
+            }
+
+            let mut widget = (self.build_row)(i);
+            let row_state = state.rows.entry(i).or_insert_with(W::State::new);
+
+            if widget.accepts_focus(row_state, env)
+                && widget.move_focus(row_state, env, direction, false, ctx)
+            {
+                state.focused = Some(i);
+                state.scroll_to_row(i);
+                ctx.request_layout();
+
+                return true;
+            }
+
+            next = step(i);
+        }
+
+        state.focused = None;
+        state.forget_hidden_rows();
+        false
+    }
+
+    /// Only rows that are alive get the timer. The others have to request a new one when they
+    /// are built again.
+    fn handle_timer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        for (&i, row_state) in &mut state.rows {
+            let focus = focus && state.focused == Some(i);
+            (self.build_row)(i).handle_timer(row_state, env, token, focus, ctx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestHarness;
+    use crate::widgets::button::text_button;
+    use crate::widgets::drawables::{text, FixedRect};
+    use crate::widgets::scroll::scroll_horizontal;
+    use druid_shell::{KbKey, KeyEvent, Modifiers};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn list(count: usize) -> impl Widget<Vec<usize>, State = impl WidgetState> {
+        virtual_list(count, 20., |i| {
+            text_button("Row", move |clicked: &mut Vec<usize>| clicked.push(i))
+        })
+    }
+
+    #[test]
+    fn only_visible_rows_live() {
+        let mut harness = TestHarness::with_env(list(10_000), Vec::new(), (100., 100.));
+
+        harness.click((40., 50.));
+        assert_eq!(*harness.env(), [2]);
+
+        harness.wheel((40., 50.), (0., 1000.));
+        harness.click((40., 50.));
+        assert_eq!(*harness.env(), [2, 52]);
+
+        harness.wheel((40., 50.), (0., 1_000_000.));
+        harness.click((40., 90.));
+        assert_eq!(*harness.env(), [2, 52, 9999]);
+    }
+
+    #[test]
+    fn focus_scrolls_into_view() {
+        let mut harness = TestHarness::with_env(list(100), Vec::new(), (100., 100.));
+
+        for _ in 0..7 {
+            harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        }
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Enter));
+        assert_eq!(*harness.env(), [6]);
+
+        // row 6 ends at 140, so it is at the bottom of the viewport now
+        harness.click((40., 90.));
+        assert_eq!(*harness.env(), [6, 6]);
+    }
+
+    #[test]
+    fn focus_search_only_builds_nearby_rows() {
+        let built = Rc::new(Cell::new(0));
+        let counter = built.clone();
+        let list = virtual_list(10_000, 20., move |_| {
+            counter.set(counter.get() + 1);
+            text("Row")
+        });
+        let mut harness = TestHarness::with_env(list, (), (100., 100.));
+        built.set(0);
+
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        assert!(built.get() < 100, "built {} rows", built.get());
+    }
+
+    #[test]
+    fn rows_get_the_wheel_first() {
+        let list = virtual_list(100, 20., |_| scroll_horizontal(FixedRect(Size::new(300., 10.))));
+        let mut harness = TestHarness::with_env(list, (), (100., 100.));
+
+        harness.wheel((20., 10.), (20., 20.));
+        assert_eq!(harness.state().offset(), 0.);
+
+        // once the row can't scroll any further the list scrolls
+        harness.wheel((20., 10.), (10_000., 0.));
+        harness.wheel((20., 10.), (20., 20.));
+        assert_eq!(harness.state().offset(), 20.);
+    }
+}
@@ -0,0 +1,667 @@
+use super::flex::{BaselineExtent, CrossPlacement, MainPlacement};
+use super::*;
+
+/// Places the children left to right and starts a new line, called a run, whenever the next one
+/// doesn't fit in the width anymore. The children keep the size they ask for, so the flex factors
+/// of the items don't matter here.
+pub fn wrap<C>(content: C) -> Wrap<C> {
+    Wrap {
+        content,
+        spacing: 0.,
+        run_spacing: 0.,
+        alignment: MainAxisAlignment::Start,
+        cross_axis_alignment: CrossAxisAlignment::Start,
+    }
+}
+
+pub struct Wrap<C> {
+    content: C,
+    spacing: f64,
+    run_spacing: f64,
+    alignment: MainAxisAlignment,
+    cross_axis_alignment: CrossAxisAlignment,
+}
+
+impl<C> Wrap<C> {
+    /// The space between two neighbouring children in a run.
+    pub fn spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// The space between two runs.
+    pub fn run_spacing(mut self, spacing: f64) -> Self {
+        self.run_spacing = spacing;
+        self
+    }
+
+    /// Where to put the children of each run in the width that is left over on its line.
+    pub fn alignment(mut self, alignment: MainAxisAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// How to place children that are less high than their run. By default they are at the top.
+    /// Stretched children are laid out again with the height of their run.
+    pub fn cross_axis_alignment(mut self, alignment: CrossAxisAlignment) -> Self {
+        self.cross_axis_alignment = alignment;
+        self
+    }
+}
+
+/// A line of children.
+#[derive(Copy, Clone, Default, Debug)]
+struct Run {
+    count: u32,
+    /// The width of the children without the spacing between them.
+    length: f64,
+    height: f64,
+    baselines: BaselineExtent,
+}
+
+impl Run {
+    fn width(&self, spacing: f64) -> f64 {
+        self.length + spacing * self.count.saturating_sub(1) as f64
+    }
+}
+
+/// Splits children into runs one after the other.
+#[derive(Default, Debug)]
+struct Runs {
+    runs: Vec<Run>,
+    max_width: f64,
+    spacing: f64,
+}
+
+impl Runs {
+    fn add(&mut self, size: Size, baseline: Option<f64>, align_baselines: bool) {
+        let fits = match self.runs.last() {
+            Some(run) => run.width(self.spacing) + self.spacing + size.width <= self.max_width,
+            None => false,
+        };
+
+        if !fits {
+            self.runs.push(Run::default());
+        }
+
+        let run = self.runs.last_mut().unwrap();
+        run.count += 1;
+        run.length += size.width;
+        run.height = run.height.max(size.height);
+
+        if align_baselines {
+            run.baselines.add(size.height, baseline);
+            run.height = run.height.max(run.baselines.height());
+        }
+    }
+}
+
+/// Hands out the rects of the children in the order they were measured in.
+struct RunPlacer<'r> {
+    runs: &'r [Run],
+    alignment: MainAxisAlignment,
+    cross_axis_alignment: CrossAxisAlignment,
+    spacing: f64,
+    run_spacing: f64,
+    rect: Rect,
+    run: usize,
+    in_run: u32,
+    pos: Point,
+    main: MainPlacement,
+}
+
+impl<'r> RunPlacer<'r> {
+    fn new<C>(wrap: &Wrap<C>, runs: &'r [Run], rect: Rect) -> Self {
+        let mut placer = RunPlacer {
+            runs,
+            alignment: wrap.alignment,
+            cross_axis_alignment: wrap.cross_axis_alignment,
+            spacing: wrap.spacing,
+            run_spacing: wrap.run_spacing,
+            rect,
+            run: 0,
+            in_run: 0,
+            pos: rect.origin(),
+            main: MainPlacement {
+                lead: 0.,
+                gap: 0.,
+                flex_unit: 0.,
+            },
+        };
+
+        placer.start_run();
+        placer
+    }
+
+    fn start_run(&mut self) {
+        if let Some(run) = self.runs.get(self.run) {
+            self.main = MainPlacement::new(
+                self.alignment,
+                self.rect.width(),
+                run.length,
+                0.,
+                self.spacing,
+                run.count,
+            );
+            self.pos.x = self.rect.x0 + self.main.lead;
+        }
+    }
+
+    fn next(&mut self, size: Size, baseline: Option<f64>) -> Rect {
+        while let Some(run) = self.runs.get(self.run) {
+            if self.in_run < run.count {
+                break;
+            }
+
+            self.pos.y += run.height + self.run_spacing;
+            self.run += 1;
+            self.in_run = 0;
+            self.start_run();
+        }
+
+        // the content changed since the last layout, so there's nowhere to put this one
+        let run = match self.runs.get(self.run) {
+            Some(run) => run,
+            None => return Rect::from_origin_size(self.pos, Size::ZERO),
+        };
+
+        let cross = CrossPlacement {
+            alignment: self.cross_axis_alignment,
+            baseline: run.baselines.ascent,
+        };
+        let (offset, height) = cross.place(run.height, size.height, baseline);
+
+        let rect = Rect::from_origin_size(
+            (self.pos.x, self.pos.y + offset),
+            (size.width, height),
+        );
+
+        self.pos.x += size.width + self.main.gap;
+        self.in_run += 1;
+
+        rect
+    }
+}
+
+pub struct WrapState<S> {
+    content_state: S,
+
+    size: Size,
+    runs: Vec<Run>,
+    extra_layers: u8,
+}
+
+impl<S: FlexContentState> WidgetState for WrapState<S> {
+    fn new() -> Self {
+        WrapState {
+            content_state: S::new(),
+            size: Size::ZERO,
+            runs: Vec::new(),
+            extra_layers: 0,
+        }
+    }
+
+    fn min_size(&self) -> Size {
+        self.size
+    }
+
+    fn extra_layers(&self) -> u8 {
+        self.extra_layers
+    }
+
+    /// The baseline of the first run, when the children are aligned on their baselines.
+    fn baseline(&self) -> Option<f64> {
+        self.runs.first().and_then(|run| run.baselines.ascent)
+    }
+}
+
+impl<E, C: FlexContent<E>> Widget<E> for Wrap<C> {
+    type State = WrapState<C::State>;
+
+    fn layout(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        constraint: LayoutConstraint,
+        ctx: &mut LayoutCtx,
+    ) {
+        struct MeasureHandler<'a, 'b, 't, E> {
+            env: &'a mut E,
+            constraint: LayoutConstraint,
+            ctx: &'a mut LayoutCtx<'b, 't>,
+            runs: Runs,
+            align_baselines: bool,
+            extra_layers: u8,
+        }
+
+        impl<'a, 'b, 't, E> FlexContentHandler<E> for MeasureHandler<'a, 'b, 't, E> {
+            fn widget<W: Widget<E>>(
+                &mut self,
+                widget: &mut W,
+                state: &mut W::State,
+                _: f64,
+                _: &mut bool,
+                layout: &mut LayoutCache,
+            ) {
+                layout.layout(widget, state, self.env, self.constraint, self.ctx);
+
+                self.runs.add(state.min_size(), state.baseline(), self.align_baselines);
+                self.extra_layers = self.extra_layers.max(state.extra_layers());
+            }
+        }
+
+        // The children can be as high as they like, but none of them is given more than a line.
+        // Without a width limit everything ends up in one run.
+        let mut handler = MeasureHandler {
+            env: &mut *env,
+            constraint: LayoutConstraint::new(
+                Size::ZERO,
+                Size::new(constraint.max.width, f64::INFINITY),
+            ),
+            ctx: &mut *ctx,
+            runs: Runs {
+                runs: Vec::new(),
+                max_width: constraint.max.width,
+                spacing: self.spacing,
+            },
+            align_baselines: self.cross_axis_alignment == CrossAxisAlignment::Baseline,
+            extra_layers: 0,
+        };
+
+        self.content.all(&mut state.content_state, &mut handler);
+
+        state.runs = handler.runs.runs;
+        state.extra_layers = handler.extra_layers;
+
+        if self.cross_axis_alignment == CrossAxisAlignment::Stretch {
+            struct StretchHandler<'a, 'b, 't, 'r, E> {
+                env: &'a mut E,
+                ctx: &'a mut LayoutCtx<'b, 't>,
+                runs: &'r [Run],
+                run: usize,
+                in_run: u32,
+            }
+
+            impl<'a, 'b, 't, 'r, E> FlexContentHandler<E> for StretchHandler<'a, 'b, 't, 'r, E> {
+                fn widget<W: Widget<E>>(
+                    &mut self,
+                    widget: &mut W,
+                    state: &mut W::State,
+                    _: f64,
+                    _: &mut bool,
+                    layout: &mut LayoutCache,
+                ) {
+                    while let Some(run) = self.runs.get(self.run) {
+                        if self.in_run < run.count {
+                            break;
+                        }
+
+                        self.run += 1;
+                        self.in_run = 0;
+                    }
+
+                    if let Some(run) = self.runs.get(self.run) {
+                        let size = Size::new(state.min_size().width, run.height);
+                        layout.layout(
+                            widget,
+                            state,
+                            self.env,
+                            LayoutConstraint::new(size, size),
+                            self.ctx,
+                        );
+                    }
+
+                    self.in_run += 1;
+                }
+            }
+
+            // the run heights are only known once every child was measured
+            let mut handler = StretchHandler {
+                env,
+                ctx,
+                runs: &state.runs,
+                run: 0,
+                in_run: 0,
+            };
+
+            self.content.all(&mut state.content_state, &mut handler);
+        }
+
+        let width = state
+            .runs
+            .iter()
+            .map(|run| run.width(self.spacing))
+            .fold(0., f64::max);
+        let height = state.runs.iter().map(|run| run.height).sum::<f64>()
+            + self.run_spacing * state.runs.len().saturating_sub(1) as f64;
+
+        // a single child that is wider than a line goes beyond the maximum
+        state.size = constraint.constrain_min(Size::new(width, height));
+    }
+
+    fn render(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        layer: u8,
+        focus: bool,
+        ctx: &mut RenderCtx,
+    ) {
+        struct RenderHandler<'a, 'b, 'c, 't, 'is, 'r, E> {
+            env: &'a mut E,
+            placer: RunPlacer<'r>,
+            layer: u8,
+            extra_layers: u8,
+            focus: bool,
+            ctx: &'a mut RenderCtx<'b, 'c, 't, 'is>,
+        }
+
+        impl<'a, 'b, 'c, 't, 'is, 'r, E> FlexContentHandler<E>
+            for RenderHandler<'a, 'b, 'c, 't, 'is, 'r, E>
+        {
+            fn widget<W: Widget<E>>(
+                &mut self,
+                widget: &mut W,
+                state: &mut W::State,
+                _: f64,
+                focus: &mut bool,
+                _: &mut LayoutCache,
+            ) {
+                let rect = self.placer.next(state.min_size(), state.baseline());
+
+                if self.layer <= state.extra_layers() {
+                    let empty_input_state: InputState = Default::default();
+
+                    let input_state = if self.layer == self.extra_layers {
+                        self.ctx.input_state
+                    } else {
+                        &empty_input_state
+                    };
+
+                    widget.render(
+                        state,
+                        self.env,
+                        rect,
+                        self.layer,
+                        self.focus && *focus,
+                        &mut RenderCtx {
+                            piet: &mut *self.ctx.piet,
+                            input_state: &input_state,
+                            ..*self.ctx
+                        },
+                    );
+                }
+            }
+        }
+
+        let mut handler = RenderHandler {
+            env,
+            placer: RunPlacer::new(self, &state.runs, rect),
+            layer,
+            extra_layers: state.extra_layers,
+            focus,
+            ctx,
+        };
+
+        self.content.all(&mut state.content_state, &mut handler);
+    }
+
+    fn handle_cursor_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        cursor_pos: Point,
+        cursor_layer: u8,
+        input: CursorInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> InputReturn {
+        struct CursorInputHandler<'a, 'b, 'r, E> {
+            env: &'a mut E,
+            placer: RunPlacer<'r>,
+            cursor_pos: Point,
+            cursor_layer: u8,
+            theme: &'a Theme,
+            input: CursorInput,
+            input_state: &'a InputState,
+            focus: bool,
+            i: u16,
+            demand_focus: Option<u16>,
+            ctx: &'a mut InputCtx<'b>,
+        }
+
+        impl<'a, 'b, 'r, E> FlexContentHandler<E> for CursorInputHandler<'a, 'b, 'r, E> {
+            fn widget<W: Widget<E>>(
+                &mut self,
+                widget: &mut W,
+                state: &mut W::State,
+                _: f64,
+                focus: &mut bool,
+                layout: &mut LayoutCache,
+            ) {
+                let rect = self.placer.next(state.min_size(), state.baseline());
+
+                if state.extra_layers() >= self.cursor_layer {
+                    let child_focus = self.focus && *focus;
+
+                    let ret = layout.track(self.ctx, |ctx| {
+                        widget.handle_cursor_input(
+                            state,
+                            self.env,
+                            rect,
+                            self.cursor_pos,
+                            self.cursor_layer,
+                            self.input,
+                            self.input_state,
+                            self.theme,
+                            child_focus,
+                            ctx,
+                        )
+                    });
+
+                    if ret.demand_focus {
+                        *focus = true;
+                        self.demand_focus = Some(self.i);
+                    }
+                }
+
+                self.i += 1;
+            }
+        }
+
+        let mut handler = CursorInputHandler {
+            env,
+            placer: RunPlacer::new(self, &state.runs, rect),
+            cursor_pos,
+            cursor_layer,
+            theme,
+            input,
+            input_state,
+            focus,
+            i: 0,
+            demand_focus: None,
+            ctx,
+        };
+
+        self.content.all(&mut state.content_state, &mut handler);
+
+        let demand_focus = handler.demand_focus;
+
+        if demand_focus.is_some() {
+            set_content_focus::<E, _>(&mut self.content, &mut state.content_state, demand_focus);
+        }
+
+        InputReturn {
+            demand_focus: demand_focus.is_some(),
+        }
+    }
+
+    fn handle_keyboard_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input: &KeyboardInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        struct KeyboardInputHandler<'a, 'b, 'r, E> {
+            env: &'a mut E,
+            placer: RunPlacer<'r>,
+            input: &'a KeyboardInput,
+            input_state: &'a InputState,
+            theme: &'a Theme,
+            focus: bool,
+            ctx: &'a mut InputCtx<'b>,
+        }
+
+        impl<'a, 'b, 'r, E> FlexContentHandler<E> for KeyboardInputHandler<'a, 'b, 'r, E> {
+            fn widget<W: Widget<E>>(
+                &mut self,
+                widget: &mut W,
+                state: &mut W::State,
+                _: f64,
+                focus: &mut bool,
+                layout: &mut LayoutCache,
+            ) {
+                let rect = self.placer.next(state.min_size(), state.baseline());
+
+                let focus = self.focus && *focus;
+                if focus {
+                    layout.track(self.ctx, |ctx| {
+                        widget.handle_keyboard_input(
+                            state,
+                            self.env,
+                            rect,
+                            self.input,
+                            self.input_state,
+                            self.theme,
+                            focus,
+                            ctx,
+                        )
+                    });
+                }
+            }
+        }
+
+        let mut handler = KeyboardInputHandler {
+            env,
+            placer: RunPlacer::new(self, &state.runs, rect),
+            input,
+            input_state,
+            theme,
+            focus,
+            ctx,
+        };
+
+        self.content.all(&mut state.content_state, &mut handler);
+    }
+
+    fn accepts_focus(&mut self, state: &mut Self::State, env: &mut E) -> bool {
+        content_accepts_focus(&mut self.content, &mut state.content_state, env)
+    }
+
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        content_move_focus(
+            &mut self.content,
+            &mut state.content_state,
+            env,
+            direction,
+            focus,
+            ctx,
+        )
+    }
+
+    fn handle_timer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        content_handle_timer(
+            &mut self.content,
+            &mut state.content_state,
+            env,
+            token,
+            focus,
+            ctx,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flex_content;
+    use crate::testing::TestHarness;
+    use crate::widgets::drawables::{FixedRect, FixedRectState};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn breaks_into_runs() {
+        let mut runs = Runs {
+            runs: Vec::new(),
+            max_width: 100.,
+            spacing: 10.,
+        };
+
+        runs.add(Size::new(40., 10.), None, false);
+        runs.add(Size::new(50., 20.), None, false);
+        runs.add(Size::new(10., 10.), None, false);
+        // wider than a line, so it gets one of its own
+        runs.add(Size::new(120., 10.), None, false);
+
+        let counts: Vec<_> = runs.runs.iter().map(|run| run.count).collect();
+        assert_eq!(counts, [2, 1, 1]);
+        assert_eq!(runs.runs[0].width(10.), 100.);
+        assert_eq!(runs.runs[0].height, 20.);
+    }
+
+    #[test]
+    fn stretch_lays_children_out_with_the_run_height() {
+        struct Recorded(FixedRect, Rc<Cell<Size>>);
+
+        impl Widget<Runtime> for Recorded {
+            type State = FixedRectState;
+
+            fn layout(
+                &mut self,
+                state: &mut Self::State,
+                env: &mut Runtime,
+                constraint: LayoutConstraint,
+                ctx: &mut LayoutCtx,
+            ) {
+                self.0.layout(state, env, constraint, ctx);
+                self.1.set(state.min_size());
+            }
+        }
+
+        let size = Rc::new(Cell::new(Size::ZERO));
+        TestHarness::new(
+            wrap(flex_content![
+                Recorded(FixedRect(Size::new(10., 10.)), size.clone()),
+                FixedRect(Size::new(10., 30.)),
+            ])
+            .cross_axis_alignment(CrossAxisAlignment::Stretch),
+            (100., 100.),
+        );
+
+        assert_eq!(size.get(), Size::new(10., 30.));
+    }
+}
@@ -3,22 +3,25 @@
 // pub mod or;
 pub mod button;
 pub mod checkbox;
+pub mod disabled;
 pub mod drawables;
 pub mod dropdown;
 pub mod dyn_stateful_widget;
 pub mod lists;
 pub mod or;
 pub mod popup;
+pub mod scroll;
 pub mod stateful_widget;
 pub mod textbox;
+pub mod with_theme;
 
 use druid_shell::kurbo;
-use druid_shell::kurbo::{Rect, Size};
+use druid_shell::kurbo::{Point, Rect, Size};
 use druid_shell::piet::RenderContext;
 use piet_common::Piet;
 
 pub use crate::theme::*;
-use crate::{InputState, LayoutConstraint, LayoutCtx, RenderCtx, Widget, WidgetState};
+use crate::{InputCtx, InputState, LayoutConstraint, LayoutCtx, RenderCtx, Widget, WidgetState};
 
 #[derive(Copy, Clone, Default)]
 pub struct NoneWidget;
@@ -48,16 +51,6 @@ impl<E> Widget<E> for NoneWidget {
     }
 }
 
-pub const PADDING: f64 = 16.0;
-
-pub const COLOR: u32 = 0x00_00_00_FF;
-pub const COLOR_HOVER: u32 = 0x33_33_33_FF;
-pub const COLOR_DOWN: u32 = 0x44_44_44_FF;
-
-pub const BORDER_WIDTH: f64 = 2.0;
-
-pub const BORDER_COLOR: u32 = 0xFF_FF_FF_FF;
-
 // pub fn measure_rect<W: Widget>(
 //     widget: &mut W,
 //     state: &mut W::State,
@@ -76,9 +69,56 @@ pub const BORDER_COLOR: u32 = 0xFF_FF_FF_FF;
 //     ) + Size::new(PADDING * 2., PADDING * 2.)
 // }
 
+/// How the user is interacting with a widget, which changes how it's drawn. Widgets keep this in
+/// their state and update it from the input they get.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Interaction {
+    /// The cursor is over the widget.
+    pub hot: bool,
+    /// A mouse button was pressed on the widget and hasn't been released yet. The widget keeps
+    /// getting the cursor input until then, even when the cursor leaves it.
+    pub active: bool,
+    /// The widget has the keyboard focus.
+    pub focused: bool,
+}
+
+impl Interaction {
+    /// Updates `hot` for cursor input at `cursor_pos` and repaints `rect` if it changed.
+    pub fn update_hot(&mut self, rect: Rect, cursor_pos: Point, ctx: &mut InputCtx) {
+        let hot = rect.contains(cursor_pos);
+
+        if hot != self.hot {
+            self.hot = hot;
+            ctx.invalidate_rect(rect);
+        }
+    }
+
+    /// The interaction as it should be drawn in `rect`. Widgets don't find out when the cursor
+    /// leaves the window or gets covered by something else, so `hot` only counts while the
+    /// cursor is actually over `rect`.
+    pub fn visible(self, rect: Rect, input_state: &InputState) -> Interaction {
+        let over = input_state
+            .cursor_pos
+            .map_or(false, |pos| rect.contains(pos));
+
+        Interaction {
+            hot: self.hot && over,
+            ..self
+        }
+    }
+}
+
+/// A cursor position no rect contains. Containers pass releases on with it when their child must
+/// not count the release as being over it, so a press still ends but doesn't click.
+pub(crate) const OUTSIDE: Point = Point::new(f64::INFINITY, f64::INFINITY);
+
+/// Draws the background and the border described by `theme` and renders `widget` inside of them.
+/// `theme` is usually picked with [WidgetTheme::get] from one of the rect themes of the [Theme] in
+/// the [RenderCtx]. A focused widget gets a ring in the focus color instead of the border.
 pub fn render_rect<E, D: Widget<E>>(
+    theme: &RectTheme,
     border: bool,
-    hover: bool,
+    interaction: Interaction,
 
     widget: &mut D,
     state: &mut D::State,
@@ -89,33 +129,52 @@ pub fn render_rect<E, D: Widget<E>>(
     ctx: &mut RenderCtx,
 ) {
     if layer == 0 {
-        let hover = hover
-            && if let Some(point) = ctx.input_state.cursor_pos {
-                rect.contains(point)
-            } else {
-                false
-            };
-
-        let brush = &ctx.piet.solid_brush(piet_common::Color::Rgba32(
-            match (hover, ctx.input_state.mouse_down) {
-                (true, true) => COLOR_DOWN,
-                (true, false) => COLOR_HOVER,
-                (false, _) => COLOR,
-            },
-        ));
-        ctx.piet.fill(rect, brush);
-
-        if border {
-            let rect_pos = (rect.x0 + BORDER_WIDTH / 2.0, rect.y0 + BORDER_WIDTH / 2.0);
-            let rect_size = (rect.width() - BORDER_WIDTH, rect.height() - BORDER_WIDTH);
-            let rect_shape = kurbo::Rect::from_origin_size(rect_pos, rect_size);
-
-            let brush = ctx
-                .piet
-                .solid_brush(piet_common::Color::Rgba32(BORDER_COLOR));
-            ctx.piet.stroke(rect_shape, &brush, BORDER_WIDTH);
+        let Interaction {
+            hot,
+            active,
+            focused,
+        } = interaction.visible(rect, ctx.input_state);
+
+        let shape = rect_shape(theme, rect);
+
+        let background = state_color(theme.background_color, hot, active);
+        ctx.piet.fill(shape, &piet_common::Color::Rgba32(background));
+
+        if focused {
+            // a bit wider than the border, so it also shows with a border of the same color
+            let width = theme.border_width.max(1) as f64 + 1.;
+            ctx.piet
+                .stroke(shape, &piet_common::Color::Rgba32(theme.focus_color), width);
+        } else if border && theme.border_width > 0 {
+            let color = state_color(theme.border_color, hot, active);
+            ctx.piet.stroke(
+                shape,
+                &piet_common::Color::Rgba32(color),
+                theme.border_width as f64,
+            );
         }
     }
 
-    widget.render(state, env, rect.inset(-PADDING), layer, focus, ctx);
+    widget.render(
+        state,
+        env,
+        rect.inset(-theme.full_border_width()),
+        layer,
+        focus,
+        ctx,
+    );
+}
+
+/// The outline of the background of a widget drawn with `theme` in `rect`. The border is drawn
+/// along it as well.
+pub fn rect_shape(theme: &RectTheme, rect: Rect) -> kurbo::RoundedRect {
+    let rect = rect.inset(-(theme.margin as f64));
+
+    let radius = match theme.shape {
+        RectShape::Square => 0.,
+        // the corners can't be any rounder than half the shorter side
+        RectShape::Round(radius) => (radius as f64).min(rect.width().min(rect.height()) / 2.),
+    };
+
+    rect.to_rounded_rect(radius)
 }
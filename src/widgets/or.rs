@@ -1,10 +1,14 @@
 use druid_shell::kurbo;
 use druid_shell::kurbo::{Rect, Size};
+use druid_shell::TimerToken;
 use piet_common::Piet;
 
 use super::NoneWidget;
 pub use crate::theme::*;
-use crate::{InputState, LayoutConstraint, LayoutCtx, RenderCtx, Widget, WidgetState};
+use crate::{
+    FocusDirection, InputCtx, InputState, LayoutConstraint, LayoutCtx, RenderCtx, Widget,
+    WidgetState,
+};
 
 /// More variants can be added here in the future. This is obviously a
 /// suboptimal solution for the problem, the best solution would require
@@ -117,6 +121,16 @@ impl<A: WidgetState, B: WidgetState, C: WidgetState, D: WidgetState> WidgetState
             Self::D(s) => s.extra_layers(),
         }
     }
+
+    fn baseline(&self) -> Option<f64> {
+        match self {
+            Self::None => panic!(),
+            Self::A(s) => s.baseline(),
+            Self::B(s) => s.baseline(),
+            Self::C(s) => s.baseline(),
+            Self::D(s) => s.baseline(),
+        }
+    }
 }
 
 impl<E, A, B, C, D> Widget<E> for OrWidget<A, B, C, D>
@@ -222,6 +236,7 @@ where
         input_state: &InputState,
         theme: &Theme,
         focus: bool,
+        ctx: &mut InputCtx,
     ) -> crate::InputReturn {
         use OrWidget::*;
 
@@ -236,6 +251,7 @@ where
                 input_state,
                 theme,
                 focus,
+                ctx,
             ),
             B(w) => w.handle_cursor_input(
                 state.as_b_mut().unwrap(),
@@ -247,6 +263,7 @@ where
                 input_state,
                 theme,
                 focus,
+                ctx,
             ),
             C(w) => w.handle_cursor_input(
                 state.as_c_mut().unwrap(),
@@ -258,6 +275,7 @@ where
                 input_state,
                 theme,
                 focus,
+                ctx,
             ),
             D(w) => w.handle_cursor_input(
                 state.as_d_mut().unwrap(),
@@ -269,6 +287,7 @@ where
                 input_state,
                 theme,
                 focus,
+                ctx,
             ),
         }
     }
@@ -282,6 +301,7 @@ where
         input_state: &InputState,
         theme: &Theme,
         focus: bool,
+        ctx: &mut InputCtx,
     ) {
         use OrWidget::*;
 
@@ -295,6 +315,7 @@ where
                     input_state,
                     theme,
                     focus,
+                    ctx,
                 );
             }
             B(w) => {
@@ -306,6 +327,7 @@ where
                     input_state,
                     theme,
                     focus,
+                    ctx,
                 );
             }
             C(w) => {
@@ -317,6 +339,7 @@ where
                     input_state,
                     theme,
                     focus,
+                    ctx,
                 );
             }
             D(w) => {
@@ -328,8 +351,56 @@ where
                     input_state,
                     theme,
                     focus,
+                    ctx,
                 );
             }
         }
     }
+
+    fn accepts_focus(&mut self, state: &mut Self::State, env: &mut E) -> bool {
+        use OrWidget::*;
+
+        match self {
+            A(w) => w.accepts_focus(state.as_a_mut().unwrap(), env),
+            B(w) => w.accepts_focus(state.as_b_mut().unwrap(), env),
+            C(w) => w.accepts_focus(state.as_c_mut().unwrap(), env),
+            D(w) => w.accepts_focus(state.as_d_mut().unwrap(), env),
+        }
+    }
+
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        use OrWidget::*;
+
+        match self {
+            A(w) => w.move_focus(state.as_a_mut().unwrap(), env, direction, focus, ctx),
+            B(w) => w.move_focus(state.as_b_mut().unwrap(), env, direction, focus, ctx),
+            C(w) => w.move_focus(state.as_c_mut().unwrap(), env, direction, focus, ctx),
+            D(w) => w.move_focus(state.as_d_mut().unwrap(), env, direction, focus, ctx),
+        }
+    }
+
+    fn handle_timer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        use OrWidget::*;
+
+        match self {
+            A(w) => w.handle_timer(state.as_a_mut().unwrap(), env, token, focus, ctx),
+            B(w) => w.handle_timer(state.as_b_mut().unwrap(), env, token, focus, ctx),
+            C(w) => w.handle_timer(state.as_c_mut().unwrap(), env, token, focus, ctx),
+            D(w) => w.handle_timer(state.as_d_mut().unwrap(), env, token, focus, ctx),
+        }
+    }
 }
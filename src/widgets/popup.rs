@@ -47,6 +47,10 @@ impl<B: WidgetState, Q: WidgetState> WidgetState for State<B, Q> {
     fn extra_layers(&self) -> u8 {
         self.extra_layers
     }
+
+    fn baseline(&self) -> Option<f64> {
+        self.base.baseline()
+    }
 }
 
 impl<E, B: Widget<E>, P: Widget<E>, C: Fn(&mut E)> Widget<E> for Popup<B, P, C> {
@@ -64,16 +68,9 @@ impl<E, B: Widget<E>, P: Widget<E>, C: Fn(&mut E)> Widget<E> for Popup<B, P, C>
         state.extra_layers = state.base.extra_layers();
 
         if let Some(ref mut popup) = self.popup {
-            let popup_state = if let Some(ref mut state) = state.popup {
-                popup.layout(state, env, LayoutConstraint { x: None, y: None }, ctx);
-                state
-            } else {
-                state.popup = Some(P::State::new());
-                let popup_state = state.popup.as_mut().unwrap();
-
-                popup.layout(popup_state, env, constraint, ctx);
-                popup_state
-            };
+            // the popup is shown in the rect of the base, so it gets the same space
+            let popup_state = state.popup.get_or_insert_with(P::State::new);
+            popup.layout(popup_state, env, constraint, ctx);
 
             state.extra_layers += 1 + popup_state.extra_layers();
         } else {
@@ -137,6 +134,7 @@ impl<E, B: Widget<E>, P: Widget<E>, C: Fn(&mut E)> Widget<E> for Popup<B, P, C>
         input_state: &InputState,
         theme: &Theme,
         focus: bool,
+        ctx: &mut InputCtx,
     ) -> InputReturn {
         if let Some(ref mut popup) = self.popup {
             let popup_state = state.popup.as_mut().unwrap();
@@ -150,6 +148,7 @@ impl<E, B: Widget<E>, P: Widget<E>, C: Fn(&mut E)> Widget<E> for Popup<B, P, C>
 
             if click_outside_popup {
                 (self.on_close)(env);
+                ctx.invalidate();
             } else {
                 popup.handle_cursor_input(
                     popup_state,
@@ -161,6 +160,7 @@ impl<E, B: Widget<E>, P: Widget<E>, C: Fn(&mut E)> Widget<E> for Popup<B, P, C>
                     input_state,
                     theme,
                     focus,
+                    ctx,
                 );
             }
         } else {
@@ -174,9 +174,93 @@ impl<E, B: Widget<E>, P: Widget<E>, C: Fn(&mut E)> Widget<E> for Popup<B, P, C>
                 input_state,
                 theme,
                 focus,
+                ctx,
             );
         }
 
         Default::default()
     }
+
+    fn handle_keyboard_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input: &KeyboardInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        if let (Some(popup), Some(popup_state)) = (&mut self.popup, &mut state.popup) {
+            let popup_rect = popup_rect(rect, popup_state.min_size());
+
+            popup.handle_keyboard_input(
+                popup_state,
+                env,
+                popup_rect,
+                input,
+                input_state,
+                theme,
+                focus,
+                ctx,
+            );
+        } else {
+            self.base.handle_keyboard_input(
+                &mut state.base,
+                env,
+                rect,
+                input,
+                input_state,
+                theme,
+                focus,
+                ctx,
+            );
+        }
+    }
+
+    fn accepts_focus(&mut self, state: &mut Self::State, env: &mut E) -> bool {
+        if let (Some(popup), Some(popup_state)) = (&mut self.popup, &mut state.popup) {
+            popup.accepts_focus(popup_state, env)
+        } else {
+            self.base.accepts_focus(&mut state.base, env)
+        }
+    }
+
+    /// While the popup is open the focus stays inside of it.
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        if let (Some(popup), Some(popup_state)) = (&mut self.popup, &mut state.popup) {
+            popup.move_focus(popup_state, env, direction, focus, ctx)
+                || popup.move_focus(popup_state, env, direction, false, ctx)
+        } else {
+            self.base
+                .move_focus(&mut state.base, env, direction, focus, ctx)
+        }
+    }
+
+    /// The base keeps getting timers while the popup is open, just without the focus.
+    fn handle_timer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        let popup_open = state.popup.is_some();
+
+        self.base
+            .handle_timer(&mut state.base, env, token, focus && !popup_open, ctx);
+
+        if let (Some(popup), Some(popup_state)) = (&mut self.popup, &mut state.popup) {
+            popup.handle_timer(popup_state, env, token, focus, ctx);
+        }
+    }
 }
@@ -0,0 +1,646 @@
+use druid_shell::kurbo::{Point, Rect, Size, Vec2};
+
+use super::*;
+use crate::*;
+
+pub const SCROLLBAR_WIDTH: f64 = 8.0;
+pub const MIN_THUMB_LENGTH: f64 = 16.0;
+
+/// Shows `child` in a viewport that scrolls vertically. The child gets laid out without a height
+/// constraint.
+pub fn scroll<E, W: Widget<E>>(child: W) -> impl Widget<E, State = ScrollState<W::State>> {
+    Scroll {
+        child,
+        horizontal: false,
+        vertical: true,
+    }
+}
+
+/// Like [scroll], but scrolls horizontally.
+pub fn scroll_horizontal<E, W: Widget<E>>(
+    child: W,
+) -> impl Widget<E, State = ScrollState<W::State>> {
+    Scroll {
+        child,
+        horizontal: true,
+        vertical: false,
+    }
+}
+
+/// Like [scroll], but scrolls in both directions.
+pub fn scroll_both<E, W: Widget<E>>(child: W) -> impl Widget<E, State = ScrollState<W::State>> {
+    Scroll {
+        child,
+        horizontal: true,
+        vertical: true,
+    }
+}
+
+pub struct Scroll<W> {
+    child: W,
+    horizontal: bool,
+    vertical: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+pub struct ScrollState<S> {
+    child: S,
+    offset: Vec2,
+    child_size: Size,
+    min_size: Size,
+    /// Set while a scrollbar thumb is being dragged, together with where on the thumb it was
+    /// grabbed.
+    drag: Option<(Axis, f64)>,
+}
+
+impl<S> ScrollState<S> {
+    pub fn offset(&self) -> Vec2 {
+        self.offset
+    }
+}
+
+impl<S: WidgetState> WidgetState for ScrollState<S> {
+    fn new() -> Self {
+        ScrollState {
+            child: S::new(),
+            offset: Vec2::ZERO,
+            child_size: Size::ZERO,
+            min_size: Size::ZERO,
+            drag: None,
+        }
+    }
+
+    fn min_size(&self) -> Size {
+        self.min_size
+    }
+
+    fn extra_layers(&self) -> u8 {
+        self.child.extra_layers()
+    }
+}
+
+impl<W> Scroll<W> {
+    /// The part of `rect` the child is visible in, which is everything except the scrollbars.
+    fn viewport(&self, rect: Rect) -> Rect {
+        Rect::new(
+            rect.x0,
+            rect.y0,
+            if self.vertical {
+                (rect.x1 - SCROLLBAR_WIDTH).max(rect.x0)
+            } else {
+                rect.x1
+            },
+            if self.horizontal {
+                (rect.y1 - SCROLLBAR_WIDTH).max(rect.y0)
+            } else {
+                rect.y1
+            },
+        )
+    }
+
+    fn max_offset<S>(&self, state: &ScrollState<S>, viewport: Rect) -> Vec2 {
+        Vec2::new(
+            (state.child_size.width - viewport.width()).max(0.),
+            (state.child_size.height - viewport.height()).max(0.),
+        )
+    }
+
+    fn clamp_offset<S>(&self, state: &mut ScrollState<S>, viewport: Rect) {
+        let max = self.max_offset(state, viewport);
+
+        state.offset = Vec2::new(
+            state.offset.x.max(0.).min(max.x),
+            state.offset.y.max(0.).min(max.y),
+        );
+    }
+
+    fn child_rect<S>(&self, state: &ScrollState<S>, viewport: Rect) -> Rect {
+        Rect::from_origin_size(
+            viewport.origin() - state.offset,
+            Size::new(
+                state.child_size.width.max(viewport.width()),
+                state.child_size.height.max(viewport.height()),
+            ),
+        )
+    }
+
+    /// The area the scrollbar for the given axis is drawn in.
+    fn track(&self, rect: Rect, axis: Axis) -> Rect {
+        let viewport = self.viewport(rect);
+
+        match axis {
+            Axis::Vertical => Rect::new(viewport.x1, viewport.y0, rect.x1, viewport.y1),
+            Axis::Horizontal => Rect::new(viewport.x0, viewport.y1, viewport.x1, rect.y1),
+        }
+    }
+
+    /// The thumb of the scrollbar for the given axis, if the content overflows on it.
+    fn thumb<S>(&self, state: &ScrollState<S>, rect: Rect, axis: Axis) -> Option<Rect> {
+        let track = self.track(rect, axis);
+
+        match axis {
+            Axis::Vertical if self.vertical => {
+                thumb_rect(track, axis, state.child_size.height, state.offset.y)
+            }
+            Axis::Horizontal if self.horizontal => {
+                thumb_rect(track, axis, state.child_size.width, state.offset.x)
+            }
+            _ => None,
+        }
+    }
+
+    /// Sets the offset such that the start of the thumb ends up at `thumb_start`.
+    fn drag_thumb<S>(&self, state: &mut ScrollState<S>, rect: Rect, axis: Axis, thumb_start: f64) {
+        let track = self.track(rect, axis);
+
+        match axis {
+            Axis::Vertical => {
+                state.offset.y =
+                    thumb_start_to_offset(track, axis, state.child_size.height, thumb_start);
+            }
+            Axis::Horizontal => {
+                state.offset.x =
+                    thumb_start_to_offset(track, axis, state.child_size.width, thumb_start);
+            }
+        }
+    }
+
+    /// Scrolls by the wheel `delta`, unless a nested scroll area already used it. The wheel only
+    /// counts as used if the offset actually changed, so at the end of the content it passes on.
+    fn scroll_wheel<S>(
+        &self,
+        state: &mut ScrollState<S>,
+        rect: Rect,
+        delta: Vec2,
+        ctx: &mut InputCtx,
+    ) {
+        if ctx.wheel_handled {
+            return;
+        }
+
+        let delta = Vec2::new(
+            if self.horizontal { delta.x } else { 0. },
+            if self.vertical { delta.y } else { 0. },
+        );
+        let old_offset = state.offset;
+
+        state.offset += delta;
+        self.clamp_offset(state, self.viewport(rect));
+
+        if state.offset != old_offset {
+            ctx.wheel_handled = true;
+            ctx.invalidate_rect(rect);
+        }
+    }
+}
+
+/// The thumb of a scrollbar drawn in `track` for content of the given length that is scrolled by
+/// `offset`. The track is expected to be as long as the viewport. Returns `None` if the content
+/// fits.
+pub(crate) fn thumb_rect(track: Rect, axis: Axis, content_length: f64, offset: f64) -> Option<Rect> {
+    let track_length = match axis {
+        Axis::Horizontal => track.width(),
+        Axis::Vertical => track.height(),
+    };
+    let max_offset = content_length - track_length;
+
+    if max_offset <= 0. {
+        return None;
+    }
+
+    let length = (track_length * track_length / content_length)
+        .max(MIN_THUMB_LENGTH)
+        .min(track_length);
+    let start = (track_length - length) * offset / max_offset;
+
+    Some(match axis {
+        Axis::Horizontal => Rect::new(
+            track.x0 + start,
+            track.y0,
+            track.x0 + start + length,
+            track.y1,
+        ),
+        Axis::Vertical => Rect::new(
+            track.x0,
+            track.y0 + start,
+            track.x1,
+            track.y0 + start + length,
+        ),
+    })
+}
+
+/// The inverse of [thumb_rect]: the offset at which the thumb starts at `thumb_start`, clamped to
+/// the valid range.
+pub(crate) fn thumb_start_to_offset(
+    track: Rect,
+    axis: Axis,
+    content_length: f64,
+    thumb_start: f64,
+) -> f64 {
+    let (track_start, track_length) = match axis {
+        Axis::Horizontal => (track.x0, track.width()),
+        Axis::Vertical => (track.y0, track.height()),
+    };
+    let max_offset = content_length - track_length;
+
+    let thumb = match thumb_rect(track, axis, content_length, 0.) {
+        Some(thumb) => thumb,
+        None => return 0.,
+    };
+    let free = match axis {
+        Axis::Horizontal => track_length - thumb.width(),
+        Axis::Vertical => track_length - thumb.height(),
+    };
+
+    if free > 0. {
+        ((thumb_start - track_start) / free * max_offset)
+            .max(0.)
+            .min(max_offset)
+    } else {
+        0.
+    }
+}
+
+pub(crate) fn render_thumb(thumb: Rect, dragging: bool, ctx: &mut RenderCtx) {
+    let theme = ctx.theme.rect.get(
+        if dragging {
+            WidgetVariant::Active
+        } else {
+            WidgetVariant::Normal
+        },
+        true,
+    );
+
+    ctx.piet.fill(
+        thumb.inset(-1.).to_rounded_rect(SCROLLBAR_WIDTH / 2. - 1.),
+        &piet_common::Color::Rgba32(theme.background_color.0),
+    );
+}
+
+impl<E, W: Widget<E>> Widget<E> for Scroll<W> {
+    type State = ScrollState<W::State>;
+
+    fn layout(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        constraint: LayoutConstraint,
+        ctx: &mut LayoutCtx,
+    ) {
+        let bar_width = if self.vertical { SCROLLBAR_WIDTH } else { 0. };
+        let bar_height = if self.horizontal { SCROLLBAR_WIDTH } else { 0. };
+
+        let inner = constraint.deflate(Size::new(bar_width, bar_height));
+
+        // the child can be as big as it wants on the scrolling axes
+        self.child.layout(
+            &mut state.child,
+            env,
+            LayoutConstraint {
+                min: Size::new(
+                    if self.horizontal { 0. } else { inner.min.width },
+                    if self.vertical { 0. } else { inner.min.height },
+                ),
+                max: Size::new(
+                    if self.horizontal { f64::INFINITY } else { inner.max.width },
+                    if self.vertical { f64::INFINITY } else { inner.max.height },
+                ),
+            },
+            ctx,
+        );
+
+        state.child_size = state.child.min_size();
+
+        // On the scrolling axes we can make do with any size, otherwise we'd have to grow to the
+        // size of the child.
+        let size = state.child_size + Size::new(bar_width, bar_height);
+        let clamped = constraint.constrain(size);
+        let grown = constraint.constrain_min(size);
+
+        state.min_size = Size::new(
+            if self.horizontal { clamped.width } else { grown.width },
+            if self.vertical { clamped.height } else { grown.height },
+        );
+    }
+
+    fn render(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        layer: u8,
+        focus: bool,
+        ctx: &mut RenderCtx,
+    ) {
+        let viewport = self.viewport(rect);
+        self.clamp_offset(state, viewport);
+        let child_rect = self.child_rect(state, viewport);
+
+        // Layers above belong to popups and the like, which aren't clipped.
+        if layer > 0 {
+            self.child
+                .render(&mut state.child, env, child_rect, layer, focus, ctx);
+            return;
+        }
+
+        let mut input_state = ctx.input_state.clone();
+        if !input_state.cursor_pos.map_or(false, |p| viewport.contains(p)) {
+            input_state.cursor_pos = None;
+        }
+
+        ctx.piet.save().unwrap();
+        ctx.piet.clip(viewport);
+
+        self.child.render(
+            &mut state.child,
+            env,
+            child_rect,
+            0,
+            focus,
+            &mut RenderCtx {
+                piet: &mut *ctx.piet,
+                theme: ctx.theme,
+                input_state: &input_state,
+            },
+        );
+
+        ctx.piet.restore().unwrap();
+
+        for axis in [Axis::Vertical, Axis::Horizontal] {
+            if let Some(thumb) = self.thumb(state, rect, axis) {
+                render_thumb(thumb, matches!(state.drag, Some((a, _)) if a == axis), ctx);
+            }
+        }
+    }
+
+    fn test_input_pos_layer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input_pos: Point,
+    ) -> Option<u8> {
+        let viewport = self.viewport(rect);
+        let child_rect = self.child_rect(state, viewport);
+
+        match self
+            .child
+            .test_input_pos_layer(&mut state.child, env, child_rect, input_pos)
+        {
+            Some(layer) if layer > 0 || viewport.contains(input_pos) => Some(layer),
+            _ => {
+                if rect.contains(input_pos) {
+                    Some(0)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn handle_cursor_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        mut cursor_pos: Point,
+        cursor_layer: u8,
+        input: CursorInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> InputReturn {
+        let viewport = self.viewport(rect);
+
+        if cursor_layer == 0 {
+            match input {
+                CursorInput::Down(MouseButton::Primary) => {
+                    for axis in [Axis::Vertical, Axis::Horizontal] {
+                        if let Some(thumb) = self.thumb(state, rect, axis) {
+                            if thumb.contains(cursor_pos) {
+                                let grab = match axis {
+                                    Axis::Vertical => cursor_pos.y - thumb.y0,
+                                    Axis::Horizontal => cursor_pos.x - thumb.x0,
+                                };
+                                state.drag = Some((axis, grab));
+                                ctx.invalidate_rect(rect);
+
+                                return Default::default();
+                            }
+                        }
+                    }
+                }
+                CursorInput::Move => {
+                    if let Some((axis, grab)) = state.drag {
+                        let thumb_start = match axis {
+                            Axis::Vertical => cursor_pos.y - grab,
+                            Axis::Horizontal => cursor_pos.x - grab,
+                        };
+                        self.drag_thumb(state, rect, axis, thumb_start);
+                        ctx.invalidate_rect(rect);
+
+                        return Default::default();
+                    }
+                }
+                CursorInput::Up(..) => {
+                    if state.drag.take().is_some() {
+                        ctx.invalidate_rect(rect);
+                        return Default::default();
+                    }
+                }
+                _ => (),
+            }
+
+            // The parts of the child that are scrolled out of view must not react to the cursor.
+            if !viewport.contains(cursor_pos) {
+                // the cursor might have just left a hovered child
+                if input == CursorInput::Move {
+                    ctx.invalidate_rect(viewport);
+                }
+
+                // the wheel still scrolls over the scrollbars
+                if let CursorInput::Wheel(delta) = input {
+                    if rect.contains(cursor_pos) {
+                        self.scroll_wheel(state, rect, delta, ctx);
+                    }
+                }
+
+                // A press inside has to end even if it is released out here, but nothing in the
+                // child may count the release as being over it.
+                if !matches!(input, CursorInput::Up(..)) {
+                    return Default::default();
+                }
+
+                cursor_pos = OUTSIDE;
+            }
+        }
+
+        self.clamp_offset(state, viewport);
+        let child_rect = self.child_rect(state, viewport);
+
+        let ret = self.child.handle_cursor_input(
+            &mut state.child,
+            env,
+            child_rect,
+            cursor_pos,
+            cursor_layer,
+            input,
+            input_state,
+            theme,
+            focus,
+            ctx,
+        );
+
+        // the child gets the wheel first, so nested scroll areas scroll before this one does
+        if let CursorInput::Wheel(delta) = input {
+            if cursor_layer == 0 {
+                self.scroll_wheel(state, rect, delta, ctx);
+            }
+        }
+
+        ret
+    }
+
+    fn handle_keyboard_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input: &KeyboardInput,
+        input_state: &InputState,
+        theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        let child_rect = self.child_rect(state, self.viewport(rect));
+
+        self.child.handle_keyboard_input(
+            &mut state.child,
+            env,
+            child_rect,
+            input,
+            input_state,
+            theme,
+            focus,
+            ctx,
+        );
+    }
+
+    fn accepts_focus(&mut self, state: &mut Self::State, env: &mut E) -> bool {
+        self.child.accepts_focus(&mut state.child, env)
+    }
+
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        self.child
+            .move_focus(&mut state.child, env, direction, focus, ctx)
+    }
+
+    fn handle_timer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        self.child
+            .handle_timer(&mut state.child, env, token, focus, ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flex_content;
+    use crate::testing::TestHarness;
+    use crate::widgets::button::text_button;
+    use crate::widgets::drawables::FixedRect;
+    use crate::widgets::lists::{col, row};
+
+    #[test]
+    fn wheel_scrolls_and_clamps() {
+        let mut harness = TestHarness::new(
+            scroll(col(flex_content![
+                text_button("A", |_: &mut Runtime| ()),
+                text_button("B", |_: &mut Runtime| ()),
+                text_button("C", |_: &mut Runtime| ()),
+                text_button("D", |_: &mut Runtime| ()),
+            ])),
+            (100., 60.),
+        );
+
+        harness.wheel((50., 30.), (0., 20.));
+        assert_eq!(harness.state().offset().y, 20.);
+
+        harness.wheel((50., 30.), (0., 10_000.));
+        let max = harness.state().offset().y;
+        assert!(max > 20.);
+
+        harness.wheel((50., 30.), (0., -10_000.));
+        assert_eq!(harness.state().offset().y, 0.);
+    }
+
+    #[test]
+    fn nested_scroll_gets_the_wheel_first() {
+        let mut harness = TestHarness::new(
+            scroll_horizontal(row(flex_content![
+                scroll(col(flex_content![
+                    text_button("A", |_: &mut Runtime| ()),
+                    text_button("B", |_: &mut Runtime| ()),
+                    text_button("C", |_: &mut Runtime| ()),
+                    text_button("D", |_: &mut Runtime| ()),
+                ])),
+                FixedRect(Size::new(300., 10.)),
+            ])),
+            (100., 60.),
+        );
+
+        // the inner scroll area takes the wheel as long as it can still scroll
+        harness.wheel((20., 20.), (20., 20.));
+        harness.wheel((20., 20.), (20., 10_000.));
+        assert_eq!(harness.state().offset().x, 0.);
+
+        // at its end the wheel passes on to the outer one
+        harness.wheel((20., 20.), (20., 20.));
+        assert_eq!(harness.state().offset().x, 20.);
+    }
+
+    #[test]
+    fn releasing_outside_a_scroll_ends_the_press() {
+        let mut harness = TestHarness::with_env(
+            row(flex_content![
+                scroll(col(flex_content![
+                    text_button("A", |clicks: &mut [u32; 2]| clicks[0] += 1),
+                    text_button("B", |_: &mut [u32; 2]| ()),
+                ])),
+                text_button("Outside", |clicks: &mut [u32; 2]| clicks[1] += 1),
+            ]),
+            [0, 0],
+            (200., 100.),
+        );
+
+        harness.mouse_down((5., 5.), MouseButton::Primary);
+        harness.move_to((150., 50.));
+        harness.mouse_up((150., 50.), MouseButton::Primary);
+        assert_eq!(*harness.env(), [0, 0]);
+
+        // A must not still think it's pressed when a press from somewhere else ends over it
+        harness.mouse_down((150., 50.), MouseButton::Primary);
+        harness.move_to((5., 5.));
+        harness.mouse_up((5., 5.), MouseButton::Primary);
+        assert_eq!(*harness.env(), [0, 0]);
+    }
+}
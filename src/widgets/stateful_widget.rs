@@ -36,6 +36,10 @@ macro_rules! stateful_widget {
             fn extra_layers(&self) -> u8 {
                 self.extra_layers
             }
+
+            fn baseline(&self) -> Option<f64> {
+                self.widget_state.baseline()
+            }
         }
 
         struct StatefulWidget;
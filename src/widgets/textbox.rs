@@ -8,7 +8,7 @@ use crate::text::{
     TextInput, TextLayout,
 };
 use piet_common::kurbo::{Affine, Insets, Size};
-use piet_common::{Piet, PietText};
+use piet_common::{Piet, PietText, Text};
 // use crate::widget::prelude::*;
 // use crate::{
 //     theme, Affine, Color, Cursor, Data, FontDescriptor, HotKey, KbKey, KeyOrValue, Point, Selector,
@@ -16,12 +16,17 @@ use piet_common::{Piet, PietText};
 // };
 
 use druid_shell::TimerToken;
-use piet_common::kurbo::{Point, Vec2};
-use piet_common::{Color, RenderContext, TextAlignment};
+use piet_common::kurbo::{Line, Point, Vec2};
+use piet_common::{Color, FontFamily, RenderContext, TextAlignment};
 
+use super::{rect_shape, state_color, Interaction, TextTheme, WidgetVariant};
 use crate::*;
 
 const MAC_OR_LINUX: bool = true; //cfg!(any(target_os = "macos", target_os = "linux"));
+
+/// The width a textbox asks for when the constraint leaves it the choice.
+const PREFERRED_WIDTH: f64 = 100.;
+
 const CURSOR_BLINK_DURATION: Duration = Duration::from_millis(500);
 
 // const BEGIN_EDITING: Selector = Selector::new("druid.builtin.textbox-begin-editing");
@@ -49,16 +54,27 @@ impl<E, F: Fn(&mut E) -> &mut TextBoxContent, G: Fn(&mut E)> Widget<E> for TextB
     ) {
         // self.editor.rebuild_if_needed(renderer.text());
 
-        let width = 100.;
-        let text_insets = Insets::new(4.0, 2.0, 4.0, 2.0);
+        // the text can scroll, so we can make do with any width
+        let width = constraint.constrain(Size::new(PREFERRED_WIDTH, 0.)).width;
 
         let content = self.0(env);
 
+        content.inset = ctx
+            .theme
+            .rect_outline
+            .get(WidgetVariant::Normal, true)
+            .full_border_width();
+        let text_insets = content.text_insets();
+
+        content.set_text_theme(
+            ctx.theme.text.get(WidgetVariant::Normal, true),
+            ctx.theme.text.get(WidgetVariant::Normal, false),
+            ctx.text,
+        );
+
         content.placeholder.rebuild_if_needed(ctx.text);
-        if let Some(width) = constraint.x {
-            if content.multiline {
-                content.editor.set_wrap_width(width - text_insets.x_value());
-            }
+        if content.multiline {
+            content.editor.set_wrap_width(width - text_insets.x_value());
         }
         content.editor.rebuild_if_needed(ctx.text);
 
@@ -79,13 +95,10 @@ impl<E, F: Fn(&mut E) -> &mut TextBoxContent, G: Fn(&mut E)> Widget<E> for TextB
         );
         content.text_pos = Point::new(text_insets.x0 + content.alignment_offset, text_insets.y0);
 
-        // Not needed for now.
-        // let bottom_padding = (height - text_metrics.size.height) / 2.0;
-        // let baseline_off =
-        //     bottom_padding + (text_metrics.size.height - text_metrics.first_baseline);
-        // renderer.set_baseline_offset(baseline_off);
+        // the text is drawn at the top inset
+        state.baseline = text_insets.y0 + text_metrics.first_baseline;
 
-        state.min_size = Size::new(width, height);
+        state.min_size = constraint.constrain_min(Size::new(width, height));
     }
 
     /// Single-layer widgets can just ignore the `layer` parameter since `render` they should only
@@ -99,24 +112,27 @@ impl<E, F: Fn(&mut E) -> &mut TextBoxContent, G: Fn(&mut E)> Widget<E> for TextB
         focus: bool,
         ctx: &mut RenderCtx,
     ) {
-        // let size = renderer.size();
-        let background_color = Color::rgb8(0x3a, 0x3a, 0x3a);
-        let selection_color = Color::rgb8(0xf3, 0x00, 0x21);
-        let cursor_color = Color::WHITE;
-        let border_width = 1.;
-        let text_insets = Insets::new(4.0, 2.0, 4.0, 2.0);
+        state.rect = rect;
+
+        let rect_theme = ctx.theme.rect_outline.get(WidgetVariant::Normal, true);
+        let text_theme = ctx.theme.text.get(WidgetVariant::Normal, true);
+
+        let Interaction { hot, active, .. } = state.interaction.visible(rect, ctx.input_state);
+
+        let background_color =
+            Color::Rgba32(state_color(rect_theme.background_color, hot, active));
+        let selection_color = Color::Rgba32(text_theme.selection_color);
+        let cursor_color = Color::Rgba32(text_theme.color);
+        let border_width = rect_theme.border_width as f64;
 
         let border_color = if focus {
-            Color::rgb8(0x00, 0x8d, 0xdd)
+            Color::Rgba32(rect_theme.focus_color)
         } else {
-            Color::rgb8(0x3a, 0x3a, 0x3a)
+            Color::Rgba32(state_color(rect_theme.border_color, hot, active))
         };
 
         // Paint the background
-        let clip_rect = Size::new(rect.width() - border_width, rect.height())
-            .to_rect()
-            .inset(-border_width / 2.0)
-            .to_rounded_rect(2.);
+        let clip_rect = rect_shape(&rect_theme, rect.with_origin(Point::ZERO));
 
         // let clip_rect = rect
         //     .inset((border_width, 0.))
@@ -148,6 +164,13 @@ impl<E, F: Fn(&mut E) -> &mut TextBoxContent, G: Fn(&mut E)> Widget<E> for TextB
                         }
                     }
                     content.editor.draw(rc, text_pos);
+
+                    // Underline the text an input method is still composing
+                    for rect in content.editor.composition_rects() {
+                        let rect = rect + text_pos.to_vec2();
+                        let y = rect.y1 - 1.;
+                        rc.stroke(Line::new((rect.x0, y), (rect.x1, y)), &cursor_color, 1.);
+                    }
                     // if !data.is_empty() {
                     //     if is_focused {
                     //         for sel in self.editor.selection_rects() {
@@ -162,9 +185,7 @@ impl<E, F: Fn(&mut E) -> &mut TextBoxContent, G: Fn(&mut E)> Widget<E> for TextB
                     // }
 
                     // Paint the cursor if focused and there's no selection
-                    if focus
-                    /* && self.should_draw_cursor() */
-                    {
+                    if focus && content.should_draw_cursor() {
                         // if there's no data, we always draw the cursor based on
                         // our alignment.
                         // let cursor = if data.is_empty() {
@@ -181,7 +202,7 @@ impl<E, F: Fn(&mut E) -> &mut TextBoxContent, G: Fn(&mut E)> Widget<E> for TextB
                             // to the right edge.
                             let mut cursor = content.editor.cursor_line() + text_pos.to_vec2();
                             let dx = rect.width() + content.hscroll_offset
-                                - text_insets.x0
+                                - content.inset
                                 - cursor.p0.x;
                             if dx < 0.0 {
                                 cursor = cursor + Vec2::new(dx, 0.);
@@ -197,7 +218,9 @@ impl<E, F: Fn(&mut E) -> &mut TextBoxContent, G: Fn(&mut E)> Widget<E> for TextB
                 .unwrap();
 
                 // Paint the border
-                rc.stroke(clip_rect, &border_color, border_width);
+                if border_width > 0. {
+                    rc.stroke(clip_rect, &border_color, border_width);
+                }
 
                 Ok(())
             })
@@ -214,12 +237,16 @@ impl<E, F: Fn(&mut E) -> &mut TextBoxContent, G: Fn(&mut E)> Widget<E> for TextB
         input: CursorInput,
         input_state: &InputState,
         _theme: &Theme,
-        _focus: bool,
+        focus: bool,
+        ctx: &mut InputCtx,
     ) -> InputReturn {
         let content = self.0(env);
 
         content.suppress_adjust_hscroll = false;
 
+        state.interaction.focused = focus;
+        state.interaction.update_hot(rect, cursor_pos, ctx);
+
         match input {
             CursorInput::Down(_button) => {
                 // ctx.request_focus();
@@ -237,9 +264,12 @@ impl<E, F: Fn(&mut E) -> &mut TextBoxContent, G: Fn(&mut E)> Widget<E> for TextB
 
                     // if !mouse.focus {
                     // }
+                    state.interaction.active = true;
+                    state.interaction.focused = true;
                     content.was_focused_from_click = true;
-                    // self.reset_cursor_blink(ctx.request_timer(CURSOR_BLINK_DURATION));
+                    content.reset_cursor_blink(ctx.request_timer(CURSOR_BLINK_DURATION));
                     content.editor.click(cursor_pos, input_state.mods);
+                    ctx.invalidate_rect(rect);
 
                     return InputReturn { demand_focus: true };
                 }
@@ -253,18 +283,23 @@ impl<E, F: Fn(&mut E) -> &mut TextBoxContent, G: Fn(&mut E)> Widget<E> for TextB
                 ];
 
                 // ctx.set_cursor(&Cursor::IBeam);
-                if input_state.mouse_down
+                if state.interaction.active
                     && cursor_pos[0] >= 0.
                     && cursor_pos[1] >= 0.
                     && cursor_pos[0] <= rect.width()
                     && cursor_pos[1] <= rect.height()
                 {
                     content.editor.drag(cursor_pos, input_state.mods);
+                    ctx.invalidate_rect(rect);
                 }
                 // if ctx.is_active() {
                 //     // ctx.request_paint();
                 // }
             }
+            CursorInput::Up(_) if state.interaction.active => {
+                state.interaction.active = false;
+                ctx.invalidate_rect(rect);
+            }
             _ => (),
         }
 
@@ -275,37 +310,114 @@ impl<E, F: Fn(&mut E) -> &mut TextBoxContent, G: Fn(&mut E)> Widget<E> for TextB
         &mut self,
         state: &mut Self::State,
         env: &mut E,
-        _rect: Rect,
+        rect: Rect,
         input: &KeyboardInput,
         _input_state: &InputState,
         _theme: &Theme,
-        _focus: bool,
+        focus: bool,
+        ctx: &mut InputCtx,
     ) {
+        state.interaction.focused = focus;
+
         let content = self.0(env);
         content.suppress_adjust_hscroll = false;
 
         match input {
             KeyboardInput::KeyDown(key_event) => {
                 match key_event {
-                    // Tab and shift+tab
-                    // k_e if HotKey::new(None, KbKey::Tab).matches(k_e) => ctx.focus_next(),
-                    // k_e if HotKey::new(SysMods::Shift, KbKey::Tab).matches(k_e) => ctx.focus_prev(),
+                    // Tab and shift+tab never get here, they move the focus (see `move_focus`).
                     k_e => {
                         if let Some(edit) = BasicTextInput.handle_event(k_e) {
                             content.suppress_adjust_hscroll = matches!(edit, EditAction::SelectAll);
-                            content.editor.do_edit(edit);
-                            content.editor.update();
 
-                            self.1(env);
-                            // an explicit request update in case the selection
-                            // state has changed, but the data hasn't.
-                            // ctx.request_update();
-                            // ctx.request_paint();
+                            if content.editor.do_edit(edit, ctx.clipboard) {
+                                content.editor.update();
+
+                                self.1(env);
+                                // the change callback might have changed anything
+                                ctx.invalidate();
+                            }
                         }
                     }
                 };
-                // self.reset_cursor_blink(ctx.request_timer(CURSOR_BLINK_DURATION));
-                // ctx.request_paint();
+            }
+            // The composed text isn't final yet, so the change callback only hears about it
+            // once it's committed.
+            KeyboardInput::CompositionStart => {
+                content.editor.start_composition();
+                content.editor.update();
+                ctx.request_layout();
+            }
+            KeyboardInput::CompositionUpdate(text) => {
+                content.editor.update_composition(text);
+                content.editor.update();
+                ctx.request_layout();
+            }
+            KeyboardInput::CompositionCommit(text) => {
+                content.editor.commit_composition(text);
+                content.editor.update();
+
+                self.1(env);
+                // the change callback might have changed anything
+                ctx.invalidate();
+            }
+        }
+
+        // the caret or the selection may have moved, and the caret shows up again
+        self.0(env).reset_cursor_blink(ctx.request_timer(CURSOR_BLINK_DURATION));
+        ctx.invalidate_rect(rect);
+    }
+
+    fn accepts_focus(&mut self, _state: &mut Self::State, _env: &mut E) -> bool {
+        true
+    }
+
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        _direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        state.interaction.focused = !focus;
+        if focus {
+            return false;
+        }
+
+        let content = self.0(env);
+
+        // Focus from the keyboard selects everything, see `was_focused_from_click`.
+        if MAC_OR_LINUX {
+            content.editor.select_all();
+        }
+        content.was_focused_from_click = false;
+        content.reset_cursor_blink(ctx.request_timer(CURSOR_BLINK_DURATION));
+
+        true
+    }
+
+    fn handle_timer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        state.interaction.focused = focus;
+
+        let content = self.0(env);
+
+        if token == content.cursor_timer {
+            if focus {
+                content.cursor_on = !content.cursor_on;
+                content.cursor_timer = ctx.request_timer(CURSOR_BLINK_DURATION);
+                ctx.invalidate_rect(state.rect);
+            } else {
+                // stop blinking until the focus comes back
+                content.cursor_on = true;
+                content.cursor_timer = TimerToken::INVALID;
             }
         }
     }
@@ -439,6 +551,17 @@ pub struct TextBoxContent {
     alignment: TextAlignment,
     alignment_offset: f64,
     text_pos: Point,
+    /// How far the text is inset from the edges, taken from the theme in `layout`.
+    inset: f64,
+    /// The text theme that was last applied to the editor and the placeholder. It only sets the
+    /// properties that weren't set with the builder methods.
+    text_theme: Option<TextTheme>,
+    /// true if the font was set with [`TextBoxContent::set_font`].
+    font_set: bool,
+    /// The size set with [`TextBoxContent::set_text_size`], kept when the font changes.
+    text_size: Option<f64>,
+    /// true if the color was set with [`TextBoxContent::set_text_color`].
+    text_color_set: bool,
     /// true if a click event caused us to gain focus.
     ///
     /// On macOS, if focus happens via click then we set the selection based
@@ -476,34 +599,52 @@ pub struct TextBoxState {
     // /// then we select our entire contents.
     // was_focused_from_click: bool,
     min_size: Size,
+    baseline: f64,
+    /// Where the textbox was rendered last, so a blinking cursor knows what to repaint.
+    rect: Rect,
+    interaction: Interaction,
     // content: &'a mut TextBoxContent,
 }
 
 impl WidgetState for TextBoxState {
     fn new() -> Self {
-        TextBoxState { min_size: Size::ZERO }
+        TextBoxState {
+            min_size: Size::ZERO,
+            baseline: 0.,
+            rect: Rect::ZERO,
+            interaction: Default::default(),
+        }
     }
 
     fn min_size(&self) -> Size {
         self.min_size
     }
+
+    fn baseline(&self) -> Option<f64> {
+        Some(self.baseline)
+    }
 }
 
 impl Default for TextBoxContent {
     fn default() -> Self {
-        let mut placeholder = TextLayout::from_text("");
-        placeholder.set_text_color(Color::rgb8(0x80, 0x80, 0x80));
+        let placeholder = TextLayout::from_text("");
         Self {
             editor: Editor::new(),
             hscroll_offset: 0.,
             suppress_adjust_hscroll: false,
             cursor_timer: TimerToken::INVALID,
-            cursor_on: false,
+            // without a running timer the cursor is drawn steadily
+            cursor_on: true,
             placeholder,
             multiline: false,
             alignment: TextAlignment::Start,
             alignment_offset: 0.0,
             text_pos: Point::ZERO,
+            inset: 0.,
+            text_theme: None,
+            font_set: false,
+            text_size: None,
+            text_color_set: false,
             was_focused_from_click: false,
             // min_size: Size::ZERO,
         }
@@ -513,19 +654,24 @@ impl Default for TextBoxContent {
 impl TextBoxContent {
     /// Create a new TextBox widget.
     pub fn new() -> Self {
-        let mut placeholder = TextLayout::from_text("");
-        placeholder.set_text_color(Color::rgb8(0x80, 0x80, 0x80));
+        let placeholder = TextLayout::from_text("");
         Self {
             editor: Editor::new(),
             hscroll_offset: 0.,
             suppress_adjust_hscroll: false,
             cursor_timer: TimerToken::INVALID,
-            cursor_on: false,
+            // without a running timer the cursor is drawn steadily
+            cursor_on: true,
             placeholder,
             multiline: false,
             alignment: TextAlignment::Start,
             alignment_offset: 0.0,
             text_pos: Point::ZERO,
+            inset: 0.,
+            text_theme: None,
+            font_set: false,
+            text_size: None,
+            text_color_set: false,
             was_focused_from_click: false,
             // min_size: Size::ZERO,
         }
@@ -613,7 +759,8 @@ impl TextBoxContent {
     /// [`Key<f64>`]: ../struct.Key.html
     pub fn set_text_size(&mut self, size: impl Into<f64>) {
         let size = size.into();
-        self.editor.layout_mut().set_text_size(size.clone());
+        self.text_size = Some(size);
+        self.editor.layout_mut().set_text_size(size);
         self.placeholder.set_text_size(size);
     }
 
@@ -626,9 +773,18 @@ impl TextBoxContent {
     /// [`FontDescriptor`]: ../struct.FontDescriptor.html
     /// [`Key<FontDescriptor>`]: ../struct.Key.html
     pub fn set_font(&mut self, font: impl Into<FontDescriptor>) {
-        let font = font.into();
+        self.font_set = true;
+        self.apply_font(font.into());
+    }
+
+    /// Sets the font of the editor and the placeholder, keeping an explicitly set size.
+    fn apply_font(&mut self, font: FontDescriptor) {
         self.editor.layout_mut().set_font(font.clone());
         self.placeholder.set_font(font);
+        if let Some(size) = self.text_size {
+            self.editor.layout_mut().set_text_size(size);
+            self.placeholder.set_text_size(size);
+        }
     }
 
     /// Set the [`TextAlignment`] for this `TextBox``.
@@ -663,6 +819,7 @@ impl TextBoxContent {
     /// [`request_layout`]: ../struct.EventCtx.html#method.request_layout
     /// [`Key<Color>`]: ../struct.Key.html
     pub fn set_text_color(&mut self, color: impl Into<Color>) {
+        self.text_color_set = true;
         self.editor.layout_mut().set_text_color(color);
     }
 
@@ -708,6 +865,33 @@ impl TextBoxContent {
         self.editor.rebuild_if_needed(factory);
     }
 
+    fn text_insets(&self) -> Insets {
+        Insets::uniform(self.inset)
+    }
+
+    /// Sets the font and the colors of the text from the theme if it changed since the last call.
+    /// The font, size and color set with the builder methods are kept. The placeholder is drawn
+    /// like disabled text.
+    fn set_text_theme(&mut self, theme: TextTheme, placeholder: TextTheme, text: &mut PietText) {
+        if self.text_theme.as_ref() == Some(&theme) {
+            return;
+        }
+
+        if !self.font_set {
+            let family = text.font_family(&theme.font).unwrap_or(FontFamily::SYSTEM_UI);
+            self.apply_font(FontDescriptor::new(family).with_size(theme.size as f64));
+        }
+        if !self.text_color_set {
+            self.editor
+                .layout_mut()
+                .set_text_color(Color::Rgba32(theme.color));
+        }
+        self.placeholder
+            .set_text_color(Color::Rgba32(placeholder.color));
+
+        self.text_theme = Some(theme);
+    }
+
     /// Calculate a stateful scroll offset
     fn update_hscroll(&mut self, self_width: f64) {
         let cursor_x = self.editor.cursor_line().p0.x;
@@ -715,7 +899,7 @@ impl TextBoxContent {
         // in its reported width, but we need to include it for these calculations.
         // see https://github.com/linebender/druid/issues/1430
         let overall_text_width = self.editor.layout().size().width.max(cursor_x);
-        let text_insets = Insets::new(4.0, 2.0, 4.0, 2.0);
+        let text_insets = self.text_insets();
 
         //// when advancing the cursor, we want some additional padding
         if overall_text_width < self_width - text_insets.x_value() {
@@ -993,3 +1177,112 @@ impl<T: TextStorage + EditableText> Widget<T> for TextBox<T> {
     }
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flex_content;
+    use crate::testing::TestHarness;
+    use crate::widgets::button::text_button;
+    use crate::widgets::lists::row;
+    use druid_shell::{KbKey, Modifiers};
+
+    #[test]
+    fn composition_is_committed_as_one_edit() {
+        let mut harness = TestHarness::with_env(
+            textbox(|e: &mut (TextBoxContent, u32)| &mut e.0, |e| e.1 += 1),
+            (TextBoxContent::new(), 0),
+            (200., 40.),
+        );
+
+        harness.keyboard_input(KeyboardInput::CompositionStart);
+        harness.keyboard_input(KeyboardInput::CompositionUpdate("k".into()));
+        harness.keyboard_input(KeyboardInput::CompositionUpdate("か".into()));
+        assert_eq!(harness.env().0.text(), "か");
+        assert_eq!(harness.env().1, 0);
+
+        harness.keyboard_input(KeyboardInput::CompositionCommit("下".into()));
+        assert_eq!(harness.env().0.text(), "下");
+        assert_eq!(harness.env().1, 1);
+
+        // a cancelled composition leaves the text as it was
+        harness.keyboard_input(KeyboardInput::CompositionUpdate("x".into()));
+        harness.keyboard_input(KeyboardInput::CompositionCommit(String::new()));
+        assert_eq!(harness.env().0.text(), "下");
+    }
+
+    #[test]
+    fn builder_text_size_outlives_the_theme() {
+        let harness = |mut content: TextBoxContent| {
+            content.set_text("Text".into());
+            TestHarness::with_env(textbox(|e: &mut TextBoxContent| e, |_| ()), content, (200., 100.))
+        };
+        let themed = harness(TextBoxContent::new());
+        let sized = harness(TextBoxContent::new().with_text_size(40.));
+
+        let height = |content: &TextBoxContent| content.editor().layout().size().height;
+        assert!(height(sized.env()) > 2. * height(themed.env()));
+    }
+
+    #[test]
+    fn copy_paste_between_textboxes() {
+        let mut harness = TestHarness::with_env(
+            row(flex_content![
+                textbox(|c: &mut [TextBoxContent; 2]| &mut c[0], |_| ()),
+                textbox(|c: &mut [TextBoxContent; 2]| &mut c[1], |_| ()),
+            ]),
+            [TextBoxContent::new(), TextBoxContent::new()],
+            (400., 100.),
+        );
+
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        harness.type_text("hi");
+        harness.key_down(KeyEvent::for_test(Modifiers::CONTROL, "a"));
+        harness.key_down(KeyEvent::for_test(Modifiers::CONTROL, "c"));
+        assert_eq!(harness.clipboard().contents.as_deref(), Some("hi"));
+
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        harness.key_down(KeyEvent::for_test(Modifiers::CONTROL, "v"));
+        harness.key_down(KeyEvent::for_test(Modifiers::CONTROL, "v"));
+        assert_eq!(harness.env()[1].text(), "hihi");
+
+        harness.key_down(KeyEvent::for_test(Modifiers::CONTROL, "z"));
+        assert_eq!(harness.env()[1].text(), "hi");
+    }
+
+    #[test]
+    fn caret_blinks_only_while_focused() {
+        let mut harness = TestHarness::with_env(
+            row(flex_content![
+                textbox(|c: &mut TextBoxContent| c, |_| ()),
+                text_button("B", |_: &mut TextBoxContent| ()),
+            ]),
+            TextBoxContent::new(),
+            (400., 100.),
+        );
+        assert_eq!(harness.pending_timers(), 0);
+
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        assert_eq!(harness.pending_timers(), 1);
+
+        // every blink requests the next one
+        harness.advance_time(Duration::from_millis(1200));
+        assert_eq!(harness.pending_timers(), 1);
+
+        // without the focus the next blink doesn't request another one
+        harness.key_down(KeyEvent::for_test(Modifiers::default(), KbKey::Tab));
+        harness.advance_time(Duration::from_millis(500));
+        assert_eq!(harness.pending_timers(), 0);
+    }
+
+    #[test]
+    fn textbox_takes_the_width_it_is_given() {
+        let harness = TestHarness::with_env(
+            textbox(|c: &mut TextBoxContent| c, |_| ()),
+            TextBoxContent::new(),
+            (300., 40.),
+        );
+
+        assert_eq!(harness.size().width, 300.);
+    }
+}
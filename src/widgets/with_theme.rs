@@ -0,0 +1,215 @@
+use druid_shell::kurbo::{Point, Rect, Size};
+
+use super::*;
+use crate::*;
+
+/// Shows `child` with a theme derived from the one of the parent by `modifier`, like a panel with
+/// bigger text or a different accent color. The modifier runs again whenever the child is laid
+/// out, so changes to the outer theme still reach the subtree.
+pub fn with_theme<E, M: Fn(&mut Theme), W: Widget<E>>(
+    modifier: M,
+    child: W,
+) -> impl Widget<E, State = WithThemeState<W::State>> {
+    WithTheme { modifier, child }
+}
+
+pub struct WithTheme<M, W> {
+    modifier: M,
+    child: W,
+}
+
+pub struct WithThemeState<S> {
+    child: S,
+    theme: Theme,
+}
+
+impl<S: WidgetState> WidgetState for WithThemeState<S> {
+    fn new() -> Self {
+        WithThemeState {
+            child: S::new(),
+            theme: Default::default(),
+        }
+    }
+
+    fn min_size(&self) -> Size {
+        self.child.min_size()
+    }
+
+    fn extra_layers(&self) -> u8 {
+        self.child.extra_layers()
+    }
+
+    fn baseline(&self) -> Option<f64> {
+        self.child.baseline()
+    }
+}
+
+impl<E, M: Fn(&mut Theme), W: Widget<E>> Widget<E> for WithTheme<M, W> {
+    type State = WithThemeState<W::State>;
+
+    fn layout(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        constraint: LayoutConstraint,
+        ctx: &mut LayoutCtx,
+    ) {
+        state.theme = ctx.theme.clone();
+        (self.modifier)(&mut state.theme);
+
+        self.child.layout(
+            &mut state.child,
+            env,
+            constraint,
+            &mut LayoutCtx {
+                text: &mut *ctx.text,
+                theme: &state.theme,
+                timers: &mut *ctx.timers,
+                relayout: ctx.relayout,
+            },
+        );
+    }
+
+    fn render(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        layer: u8,
+        focus: bool,
+        ctx: &mut RenderCtx,
+    ) {
+        self.child.render(
+            &mut state.child,
+            env,
+            rect,
+            layer,
+            focus,
+            &mut RenderCtx {
+                piet: &mut *ctx.piet,
+                theme: &state.theme,
+                input_state: ctx.input_state,
+            },
+        );
+    }
+
+    fn test_input_pos_layer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input_pos: Point,
+    ) -> Option<u8> {
+        self.child
+            .test_input_pos_layer(&mut state.child, env, rect, input_pos)
+    }
+
+    fn handle_cursor_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        cursor_pos: Point,
+        cursor_layer: u8,
+        input: CursorInput,
+        input_state: &InputState,
+        _theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> InputReturn {
+        self.child.handle_cursor_input(
+            &mut state.child,
+            env,
+            rect,
+            cursor_pos,
+            cursor_layer,
+            input,
+            input_state,
+            &state.theme,
+            focus,
+            ctx,
+        )
+    }
+
+    fn handle_keyboard_input(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        rect: Rect,
+        input: &KeyboardInput,
+        input_state: &InputState,
+        _theme: &Theme,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        self.child.handle_keyboard_input(
+            &mut state.child,
+            env,
+            rect,
+            input,
+            input_state,
+            &state.theme,
+            focus,
+            ctx,
+        );
+    }
+
+    fn accepts_focus(&mut self, state: &mut Self::State, env: &mut E) -> bool {
+        self.child.accepts_focus(&mut state.child, env)
+    }
+
+    fn move_focus(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        direction: FocusDirection,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) -> bool {
+        self.child
+            .move_focus(&mut state.child, env, direction, focus, ctx)
+    }
+
+    fn handle_timer(
+        &mut self,
+        state: &mut Self::State,
+        env: &mut E,
+        token: TimerToken,
+        focus: bool,
+        ctx: &mut InputCtx,
+    ) {
+        self.child
+            .handle_timer(&mut state.child, env, token, focus, ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flex_content;
+    use crate::testing::TestHarness;
+    use crate::widgets::button::text_button;
+    use crate::widgets::lists::row;
+
+    #[test]
+    fn with_theme_overrides_the_subtree() {
+        let green = (0x00_FF_00_FF, 0x00_FF_00_FF, 0x00_FF_00_FF);
+
+        let mut harness = TestHarness::new(
+            row(flex_content![
+                text_button("A", |_: &mut Runtime| ()).variant(WidgetVariant::Danger),
+                with_theme(
+                    move |theme: &mut Theme| theme.rect.enabled.normal.background_color = green,
+                    text_button("B", |_: &mut Runtime| ()),
+                ),
+            ]),
+            (200., 100.),
+        );
+
+        let frame = harness.render();
+
+        let danger = Theme::default().rect.enabled.danger.background_color.0;
+        assert_eq!(frame.pixel(10, 50), danger.to_be_bytes());
+        assert_eq!(frame.pixel(110, 50), [0x00, 0xFF, 0x00, 0xFF]);
+    }
+}